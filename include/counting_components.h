@@ -0,0 +1,104 @@
+#ifndef COUNTING_COMPONENTS_H
+#define COUNTING_COMPONENTS_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Outcome of a call to the C API
+typedef enum CcStatus {
+  CC_STATUS_OK = 0,
+  // A required pointer argument was null
+  CC_STATUS_NULL_POINTER = 1,
+  // Not a valid permutation on {0,1,...,n-1}
+  CC_STATUS_INVALID_PERMUTATION = 2,
+  // Flip set not a subset of {0,1,...,n-1}
+  CC_STATUS_INVALID_FLIPSET = 3,
+  // The number of strands does not fit in 64 bits
+  CC_STATUS_OVERFLOW = 4,
+} CcStatus;
+
+// Which way a strand turns when resolving a crossing
+typedef enum CcDirection {
+  CC_DIRECTION_LEFT = 0,
+  CC_DIRECTION_RIGHT = 1,
+} CcDirection;
+
+// Opaque signed permutation, created by `cc_permutation_new` and freed by `cc_permutation_free`
+typedef struct CcPermutation CcPermutation;
+
+// An (m,n) pair of a sweep with its numbers of two-sided and one-sided components
+typedef struct CcSweepEntry {
+  uint64_t m;
+  uint64_t n;
+  uint64_t two_sided;
+  uint64_t one_sided;
+} CcSweepEntry;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last error on the calling thread, or null if there was none. The string is
+// owned by the library and stays valid until the next failing call on the same thread.
+const char *cc_last_error(void);
+
+// Builds a signed permutation from the same data as the Python constructor, where
+// `permutation[j]` is the letter sent to j, storing it in `*out`
+//
+// # Safety
+// `permutation` and `flips` must point to `length` and `flips_length` elements, and `out` must
+// be a valid pointer.
+enum CcStatus cc_permutation_new(const size_t *permutation,
+                                 size_t length,
+                                 const size_t *flips,
+                                 size_t flips_length,
+                                 bool transverse_flip,
+                                 struct CcPermutation **out);
+
+// Frees a permutation created by `cc_permutation_new`. Does nothing on null.
+//
+// # Safety
+// `perm` must be null or come from `cc_permutation_new`, and not be used afterwards.
+void cc_permutation_free(struct CcPermutation *perm);
+
+// Counts the two-sided and one-sided components of the multicurve resolved from m copies of
+// every band and n transverse strands, like `count_components_with_orientability`
+//
+// # Safety
+// `perm` must come from `cc_permutation_new`, and `two_sided` and `one_sided` must be valid
+// pointers.
+enum CcStatus cc_count_components(const struct CcPermutation *perm,
+                                  uint64_t m,
+                                  uint64_t n,
+                                  enum CcDirection direction,
+                                  uint64_t *two_sided,
+                                  uint64_t *one_sided);
+
+// Counts the components of every (m,n) pair with m+n < complexity in parallel, like
+// `count_components_upto_complexity`, skipping those with gcd(m,n) > 1 unless
+// `include_non_coprime` is set. Stores an array of `*length` entries sorted by m+n then n in
+// `*entries`, to be freed with `cc_sweep_free`.
+//
+// # Safety
+// `perm` must come from `cc_permutation_new`, and `entries` and `length` must be valid pointers.
+enum CcStatus cc_count_components_upto_complexity(const struct CcPermutation *perm,
+                                                  uint64_t complexity,
+                                                  enum CcDirection direction,
+                                                  bool include_non_coprime,
+                                                  struct CcSweepEntry **entries,
+                                                  size_t *length);
+
+// Frees the entries of a sweep returned by `cc_count_components_upto_complexity`
+//
+// # Safety
+// `entries` and `length` must be exactly as returned by the sweep, and not be used afterwards.
+void cc_sweep_free(struct CcSweepEntry *entries, size_t length);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* COUNTING_COMPONENTS_H */
@@ -0,0 +1,179 @@
+//! Sweeps running in the background
+//!
+//! `submit_sweep` starts a sweep on its own thread and returns at once with a `SweepHandle`, in
+//! the manner of `concurrent.futures.Future`, so that several sweeps can run concurrently while
+//! the interpreter stays responsive.
+
+use crate::cache::cached_component_counts;
+use crate::results::{entries, SweepEntry};
+use crate::{
+    check_sweep_bounds, complexity_order, sweep_pairs_upto, Direction, SignedPermutation,
+    SweepResult, SIGNAL_POLL_INTERVAL, THREAD_POOL,
+};
+use pyo3::exceptions::{PyRuntimeError, PyTimeoutError};
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How a background sweep ended
+#[derive(Clone)]
+enum Outcome {
+    Finished(Vec<SweepResult>),
+    Cancelled,
+    Panicked,
+}
+
+/// State shared between a handle and the thread running its sweep
+struct Shared {
+    cancelled: AtomicBool,
+    completed: AtomicUsize,
+    total: usize,
+    outcome: Mutex<Option<Outcome>>,
+    finished: Condvar,
+}
+
+/// Handle on a sweep started by `submit_sweep`
+#[pyclass]
+struct SweepHandle {
+    shared: Arc<Shared>,
+}
+
+#[pymethods]
+impl SweepHandle {
+    /// Whether the sweep has finished, been cancelled or failed
+    fn done(&self) -> bool {
+        self.shared.outcome.lock().unwrap().is_some()
+    }
+
+    /// Fraction of the (m,n) pairs of the sweep already counted, between 0 and 1
+    fn progress(&self) -> f64 {
+        if self.shared.total == 0 {
+            return 1.0;
+        }
+        self.shared.completed.load(Ordering::Relaxed) as f64 / self.shared.total as f64
+    }
+
+    /// Asks the sweep to stop, returning whether it was still running
+    fn cancel(&self) -> bool {
+        if self.done() {
+            return false;
+        }
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Whether the sweep stopped because of `cancel`
+    fn cancelled(&self) -> bool {
+        matches!(
+            *self.shared.outcome.lock().unwrap(),
+            Some(Outcome::Cancelled)
+        )
+    }
+
+    /// Waits for the sweep to finish and returns its results, in the same form as
+    /// `count_components_upto_complexity`. Raises `TimeoutError` if it is still running after
+    /// `timeout` seconds, and `concurrent.futures.CancelledError` if it was cancelled.
+    #[pyo3(signature = (timeout = None))]
+    fn result(&self, py: Python, timeout: Option<f64>) -> PyResult<Vec<SweepEntry>> {
+        let deadline = timeout.map(|seconds| Instant::now() + Duration::from_secs_f64(seconds));
+        loop {
+            let wait = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left == Duration::from_secs(0) && !self.done() {
+                        return Err(PyTimeoutError::new_err("Sweep still running"));
+                    }
+                    left.min(SIGNAL_POLL_INTERVAL)
+                }
+                None => SIGNAL_POLL_INTERVAL,
+            };
+            let shared = &self.shared;
+            let finished = py.detach(|| {
+                let outcome = shared.outcome.lock().unwrap();
+                let (outcome, _) = shared
+                    .finished
+                    .wait_timeout_while(outcome, wait, |outcome| outcome.is_none())
+                    .unwrap();
+                outcome.clone()
+            });
+            match finished {
+                Some(Outcome::Finished(results)) => return Ok(entries(results)),
+                Some(Outcome::Cancelled) => {
+                    let futures = py.import("concurrent.futures")?;
+                    let cancelled_error: Bound<'_, PyType> =
+                        futures.getattr("CancelledError")?.extract()?;
+                    return Err(PyErr::from_type(cancelled_error, "Sweep cancelled"));
+                }
+                Some(Outcome::Panicked) => return Err(PyRuntimeError::new_err("Sweep failed")),
+                None => py.check_signals()?,
+            }
+        }
+    }
+}
+
+/// Starts `count_components_upto_complexity` on a background thread and returns a `SweepHandle`
+/// on it at once. The sweep runs in the pool configured by `set_num_threads` at submission.
+#[pyfunction]
+#[pyo3(signature = (perm, complexity, direction = Direction::Left, include_non_coprime = false))]
+fn submit_sweep(
+    perm: &SignedPermutation,
+    complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+) -> PyResult<SweepHandle> {
+    check_sweep_bounds(perm, complexity)?;
+    let pairs: Vec<(u64, u64)> = sweep_pairs_upto(complexity, include_non_coprime).collect();
+    let shared = Arc::new(Shared {
+        cancelled: AtomicBool::new(false),
+        completed: AtomicUsize::new(0),
+        total: pairs.len(),
+        outcome: Mutex::new(None),
+        finished: Condvar::new(),
+    });
+    let perm = perm.clone();
+    let pool = THREAD_POOL.lock().unwrap().clone();
+
+    let state = Arc::clone(&shared);
+    thread::spawn(move || {
+        let job = || {
+            pairs
+                .into_par_iter()
+                .map(|(m, n)| {
+                    if state.cancelled.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    let counts = cached_component_counts(&perm, m, n, direction);
+                    state.completed.fetch_add(1, Ordering::Relaxed);
+                    Some(((m, n), counts))
+                })
+                .collect::<Option<Vec<SweepResult>>>()
+        };
+        let outcome = match panic::catch_unwind(AssertUnwindSafe(|| match pool {
+            Some(pool) => pool.install(job),
+            None => job(),
+        })) {
+            Ok(Some(mut results)) => {
+                results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+                Outcome::Finished(results)
+            }
+            Ok(None) => Outcome::Cancelled,
+            Err(_) => Outcome::Panicked,
+        };
+        *state.outcome.lock().unwrap() = Some(outcome);
+        state.finished.notify_all();
+    });
+
+    Ok(SweepHandle { shared })
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<SweepHandle>()?;
+    m.add_function(wrap_pyfunction!(submit_sweep, m)?)?;
+    Ok(())
+}
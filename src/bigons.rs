@@ -0,0 +1,228 @@
+//! Bigons between the transverse curve and the curve of the permutation
+//!
+//! The curves lie in the surface obtained from their regular neighborhood N by capping every
+//! boundary component with a disk, and a bigon is a cap whose boundary runs along a single side
+//! of a band and a single segment of the boundary of the annulus, between the blocks at the
+//! corners. Following the boundary of N from the bottom end of a band to an adjacent block, it
+//! stays on the bottom of the annulus unless it crosses the seam of a twisted annulus, so bigons
+//! only occur across the seam, bounded by flipped bands since the boundary comes back to the top
+//! end of the band on the other side.
+//!
+//! Pushing the band across the bigon removes both intersection points: the bands arriving at the
+//! first one and leaving the second one merge into a single band, whose flip is the parity of the
+//! flips of the three bands. If they are the same band, the component of the curve through it
+//! becomes disjoint from the transverse curve and leaves the configuration.
+
+use crate::{Multiplicity, SignedPermutation};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::HashSet;
+
+/// Pairs of blocks at the corners of the bigons, the block where the band bounding the bigon
+/// leaves first
+fn bigons(perm: &SignedPermutation) -> Vec<(usize, usize)> {
+    let length = perm.permutation.len();
+    if length < 2 {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    for (band, end) in perm.permutation.iter().copied().enumerate() {
+        // Follow the boundary of N along a side of the band to its bottom end, and then along
+        // the bottom of the annulus to the adjacent block on that side
+        for (neighbour, wraps) in [
+            ((end + length - 1) % length, end == 0),
+            ((end + 1) % length, end + 1 == length),
+        ] {
+            // The boundary arrives at the top of the annulus across the seam of a twisted
+            // annulus, and has to come back to the top end of the band on the other side
+            if wraps && perm.transverse_flip && neighbour == band && perm.flipped[band] {
+                found.push((band, end));
+            }
+        }
+    }
+    found
+}
+
+/// Removes the bigon bounded by the band leaving `start` and coming back to `end`, returning the
+/// reduced signed permutation and the orientability of the component of the curve that becomes
+/// disjoint from the transverse curve, if any
+fn remove_bigon(
+    perm: &SignedPermutation,
+    start: usize,
+    end: usize,
+) -> (SignedPermutation, Option<usize>) {
+    let length = perm.permutation.len();
+    let flipped = |band: usize| perm.flip_set.contains(&band) as usize;
+    let incoming = perm
+        .permutation
+        .iter()
+        .position(|image| *image == start)
+        .unwrap();
+
+    let (merged, disjoint) = if incoming == end {
+        (None, Some(flipped(start) ^ flipped(end)))
+    } else {
+        let flip = flipped(incoming) ^ flipped(start) ^ flipped(end);
+        (Some((incoming, perm.permutation[end], flip)), None)
+    };
+
+    // The blocks at the corners disappear, so the ones after them move down
+    let shift = |block: usize| block - (start < block) as usize - (end < block) as usize;
+    let mut permutation = Vec::with_capacity(length - 2);
+    let mut flip_set = HashSet::new();
+    for band in (0..length).filter(|band| *band != start && *band != end) {
+        let (image, flip) = match merged {
+            Some((incoming, image, flip)) if incoming == band => (image, flip),
+            _ => (perm.permutation[band], flipped(band)),
+        };
+        if flip == 1 {
+            flip_set.insert(shift(band));
+        }
+        permutation.push(shift(image));
+    }
+    let reduced = SignedPermutation::from_parts(permutation, flip_set, perm.transverse_flip);
+    (reduced, disjoint)
+}
+
+/// Outputs the bigons between the transverse curve and the curve of the permutation, each as the
+/// pair of letters at its corners, starting with the letter of the band bounding it
+#[pyfunction]
+fn find_bigons(perm: &SignedPermutation) -> Vec<(usize, usize)> {
+    bigons(perm)
+}
+
+/// Removes bigons until none is left, returning the reduced signed permutation and the
+/// orientability of each component of the curve of the permutation isotoped off the transverse
+/// curve
+fn reduce(perm: &SignedPermutation) -> (SignedPermutation, Vec<usize>) {
+    let mut reduced = perm.clone();
+    let mut disjoint = Vec::new();
+    while let Some((start, end)) = bigons(&reduced).first().copied() {
+        let (next, freed) = remove_bigon(&reduced, start, end);
+        reduced = next;
+        disjoint.extend(freed);
+    }
+    (reduced, disjoint)
+}
+
+/// Two-sided and one-sided components of m parallel copies of the components isotoped off the
+/// transverse curve: m copies of a one-sided curve resolve into m / 2 copies of the boundary of
+/// its Möbius band, and one more copy of the curve itself if m is odd
+fn disjoint_counts(disjoint: &[usize], m: u64) -> (u64, u64) {
+    disjoint
+        .iter()
+        .fold((0, 0), |(two_sided, one_sided), orientability| {
+            if *orientability == 0 {
+                (two_sided + m, one_sided)
+            } else {
+                (two_sided + m / 2, one_sided + m % 2)
+            }
+        })
+}
+
+/// Removes the bigons of `perm` before counting, returning the reduced signed permutation and the
+/// numbers of two-sided and one-sided components contributed by the m copies of the components
+/// isotoped off the transverse curve. The letters of the reduced permutation no longer match
+/// those of `perm`, so this requires a single m.
+pub(crate) fn reduce_for_counting(
+    perm: &SignedPermutation,
+    m: &Multiplicity,
+) -> PyResult<(SignedPermutation, (u64, u64))> {
+    let m = match m {
+        Multiplicity::Uniform(m) => *m,
+        Multiplicity::PerBand(_) => {
+            return Err(PyValueError::new_err(
+                "Reducing bigons requires a single multiplicity m",
+            ))
+        }
+    };
+    let (reduced, disjoint) = reduce(perm);
+    Ok((reduced, disjoint_counts(&disjoint, m)))
+}
+
+/// Removes bigons until none is left, so that the curves are in minimal position. Outputs the
+/// reduced signed permutation, together with the orientability (0 for two-sided, 1 for one-sided)
+/// of each component of the curve of the permutation isotoped off the transverse curve.
+#[pyfunction]
+fn reduce_bigons(perm: &SignedPermutation) -> (SignedPermutation, Vec<usize>) {
+    reduce(perm)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(find_bigons, m)?)?;
+    m.add_function(wrap_pyfunction!(reduce_bigons, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bigons, disjoint_counts, reduce};
+    use crate::{component_counts, Direction, SignedPermutation, SlotLayout};
+
+    /// Permutations of `0..length` in lexicographic order
+    fn permutations(length: usize) -> Vec<Vec<usize>> {
+        if length == 0 {
+            return vec![vec![]];
+        }
+        let mut result = Vec::new();
+        for smaller in permutations(length - 1) {
+            for position in 0..length {
+                let mut permutation = smaller.clone();
+                permutation.insert(position, length - 1);
+                result.push(permutation);
+            }
+        }
+        result
+    }
+
+    /// Component counts of the configuration with its bigons removed
+    fn reduced_counts(perm: &SignedPermutation, m: u64, n: u64) -> (u64, u64) {
+        let (reduced, disjoint) = reduce(perm);
+        let counts = component_counts(&reduced, &SlotLayout::Uniform(m), n, Direction::Left);
+        let extra = disjoint_counts(&disjoint, m);
+        (counts.0 + extra.0, counts.1 + extra.1)
+    }
+
+    #[test]
+    fn reducing_leaves_counts_unchanged() {
+        let mut reducible = 0;
+        for length in 1..=4 {
+            for permutation in permutations(length) {
+                for mask in 0..1 << length {
+                    for transverse_flip in [false, true] {
+                        let flips = (0..length).filter(|j| mask & (1 << j) != 0).collect();
+                        let perm = SignedPermutation::from_parts(
+                            permutation.clone(),
+                            flips,
+                            transverse_flip,
+                        );
+                        let (reduced, _) = reduce(&perm);
+                        assert!(bigons(&reduced).is_empty(), "{:?}", perm);
+                        assert_eq!(reduce(&reduced), (reduced.clone(), vec![]), "{:?}", perm);
+                        // Only configurations with bigons change when reduced
+                        let target = if bigons(&perm).is_empty() {
+                            assert_eq!(reduced, perm);
+                            &perm
+                        } else {
+                            reducible += 1;
+                            &reduced
+                        };
+                        for m in 1..=3 {
+                            for n in 0..=4 {
+                                let counts = component_counts(
+                                    target,
+                                    &SlotLayout::Uniform(m),
+                                    n,
+                                    Direction::Left,
+                                );
+                                assert_eq!(reduced_counts(target, m, n), counts, "{:?}", perm);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert!(reducible > 0);
+    }
+}
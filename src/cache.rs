@@ -0,0 +1,98 @@
+//! Optional memoization of component counts
+//!
+//! When enabled, the component counts of every uniform `(perm, m, n, direction)` computed by
+//! `count_components_with_orientability` or by a sweep are stored in a process-wide table, keyed
+//! by the permutation, its flips and transverse flip together with `(m, n)` and the direction, so
+//! that repeated calls and overlapping sweeps only trace each resolved multicurve once.
+
+use crate::checkpoint::permutation_words;
+use crate::{component_counts, Direction, SignedPermutation, SlotLayout};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+type CacheKey = (Vec<usize>, u64, u64, Direction);
+
+/// Cached counts, or `None` while the cache is disabled
+static CACHE: Mutex<Option<HashMap<CacheKey, (u64, u64)>>> = Mutex::new(None);
+/// Whether the cache is enabled, checked before taking the lock so that sweeps with the cache
+/// disabled never contend on it
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static HITS: AtomicUsize = AtomicUsize::new(0);
+static MISSES: AtomicUsize = AtomicUsize::new(0);
+
+/// Same as `component_counts` with m copies of every band, going through the cache if enabled
+pub(crate) fn cached_component_counts(
+    perm: &SignedPermutation,
+    m: u64,
+    n: u64,
+    direction: Direction,
+) -> (u64, u64) {
+    if !ENABLED.load(Ordering::Acquire) {
+        return component_counts(perm, &SlotLayout::Uniform(m), n, direction);
+    }
+    let key = match CACHE.lock().unwrap().as_ref() {
+        Some(cache) => {
+            let key = (permutation_words(perm), m, n, direction);
+            if let Some(counts) = cache.get(&key) {
+                HITS.fetch_add(1, Ordering::Relaxed);
+                return *counts;
+            }
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            Some(key)
+        }
+        None => None,
+    };
+    // The lock is released while tracing, so that parallel sweeps are not serialized
+    let counts = component_counts(perm, &SlotLayout::Uniform(m), n, direction);
+    if let Some(key) = key {
+        if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+            cache.insert(key, counts);
+        }
+    }
+    counts
+}
+
+/// Turns the cache on, or off if `enabled` is false, in which case its contents are dropped
+#[pyfunction]
+#[pyo3(signature = (enabled = true))]
+fn enable_cache(enabled: bool) {
+    let mut cache = CACHE.lock().unwrap();
+    match (enabled, cache.is_some()) {
+        (true, false) => *cache = Some(HashMap::new()),
+        (false, true) => *cache = None,
+        _ => {}
+    }
+    ENABLED.store(enabled, Ordering::Release);
+}
+
+/// Returns the number of cached results, the number of lookups answered from the cache and the
+/// number of lookups that had to be computed, as a dictionary
+#[pyfunction]
+fn cache_stats() -> BTreeMap<&'static str, usize> {
+    let entries = CACHE.lock().unwrap().as_ref().map_or(0, HashMap::len);
+    let mut stats = BTreeMap::new();
+    stats.insert("entries", entries);
+    stats.insert("hits", HITS.load(Ordering::Relaxed));
+    stats.insert("misses", MISSES.load(Ordering::Relaxed));
+    stats
+}
+
+/// Empties the cache and resets its statistics, leaving it enabled if it was
+#[pyfunction]
+fn clear_cache() {
+    if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+        cache.clear();
+    }
+    HITS.store(0, Ordering::Relaxed);
+    MISSES.store(0, Ordering::Relaxed);
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(enable_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cache, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,335 @@
+//! Crash-tolerant sweeps that periodically checkpoint completed (m,n) pairs to a file
+
+use crate::cache::cached_component_counts;
+use crate::results::{entries, SweepEntry};
+use crate::{
+    check_sweep_bounds, complexity_order, coprime_pairs_upto, run_interruptible, Direction,
+    SignedPermutation, SweepResult,
+};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread;
+
+create_exception!(counting_components, CheckpointException, PyException);
+
+/// First line of every checkpoint file
+const MAGIC: &str = "counting-components checkpoint v2";
+
+/// Enum describing possible errors when reading a checkpoint file
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CheckpointError {
+    /// Header is missing or malformed
+    InvalidHeader,
+    /// A result record could not be parsed
+    InvalidRecord(usize),
+    /// File belongs to a different permutation or complexity
+    KeyMismatch { expected: u64, found: u64 },
+}
+
+impl std::convert::From<CheckpointError> for PyErr {
+    fn from(err: CheckpointError) -> PyErr {
+        match err {
+            CheckpointError::InvalidHeader => {
+                CheckpointException::new_err("Invalid checkpoint header")
+            }
+            CheckpointError::InvalidRecord(line) => {
+                CheckpointException::new_err(format!("Invalid checkpoint record on line {}", line))
+            }
+            CheckpointError::KeyMismatch { expected, found } => {
+                CheckpointException::new_err(format!(
+                    "Checkpoint key {:016x} does not match the requested sweep {:016x}",
+                    found, expected
+                ))
+            }
+        }
+    }
+}
+
+/// FNV-1a hash of `words` continuing from `hash`
+fn fnv1a(mut hash: u64, words: impl Iterator<Item = u64>) -> u64 {
+    for word in words {
+        for byte in word.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// The permutation, its sorted flips and its transverse flip as one sequence of words, which
+/// identifies the signed permutation exactly
+pub(crate) fn permutation_words(perm: &SignedPermutation) -> Vec<usize> {
+    let mut flips: Vec<usize> = perm.flip_set.iter().copied().collect();
+    flips.sort_unstable();
+
+    let mut words = perm.permutation.clone();
+    words.push(usize::MAX);
+    words.extend(flips);
+    words.push(usize::MAX);
+    words.push(perm.transverse_flip as usize);
+    words
+}
+
+/// FNV-1a hash of `permutation_words`, stable across builds and platforms
+pub(crate) fn permutation_hash(perm: &SignedPermutation) -> u64 {
+    fnv1a(
+        0xcbf29ce484222325,
+        permutation_words(perm).into_iter().map(|word| word as u64),
+    )
+}
+
+/// Same as `permutation_hash`, also hashing the complexity
+fn checkpoint_key(perm: &SignedPermutation, complexity: u64) -> u64 {
+    fnv1a(permutation_hash(perm), std::iter::once(complexity))
+}
+
+/// Writes the header identifying the sweep a checkpoint file belongs to
+fn write_header(
+    file: &mut impl Write,
+    perm: &SignedPermutation,
+    complexity: u64,
+) -> io::Result<()> {
+    // The file stores the constructor argument, which is the inverse of the stored permutation
+    let mut images = vec![0; perm.permutation.len()];
+    for (input, output) in perm.permutation.iter().enumerate() {
+        images[*output] = input;
+    }
+    let mut flips: Vec<usize> = perm.flip_set.iter().copied().collect();
+    flips.sort_unstable();
+
+    let join = |values: &[usize]| {
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    writeln!(file, "{}", MAGIC)?;
+    writeln!(file, "key {:016x}", checkpoint_key(perm, complexity))?;
+    writeln!(file, "permutation {}", join(&images))?;
+    writeln!(file, "flips {}", join(&flips))?;
+    writeln!(file, "transverse_flip {}", perm.transverse_flip as usize)?;
+    writeln!(file, "complexity {}", complexity)?;
+    file.flush()
+}
+
+/// Parses a header field of the form `<name> <values...>`
+fn parse_field<T: FromStr>(line: Option<&str>, name: &str) -> Result<Vec<T>, CheckpointError> {
+    let mut words = line
+        .ok_or(CheckpointError::InvalidHeader)?
+        .split_whitespace();
+    if words.next() != Some(name) {
+        return Err(CheckpointError::InvalidHeader);
+    }
+    words
+        .map(|w| w.parse().map_err(|_| CheckpointError::InvalidHeader))
+        .collect()
+}
+
+/// Parses a result record of the form `<m> <n> <two_sided> <one_sided>`
+fn parse_record(line: &str) -> Option<SweepResult> {
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [m, n, two_sided, one_sided] => Some((
+            (m.parse().ok()?, n.parse().ok()?),
+            (two_sided.parse().ok()?, one_sided.parse().ok()?),
+        )),
+        _ => None,
+    }
+}
+
+/// Contents of a checkpoint file
+struct Checkpoint {
+    perm: SignedPermutation,
+    complexity: u64,
+    results: Vec<SweepResult>,
+    /// Length of the file up to the last complete record
+    valid_length: u64,
+}
+
+/// Reads a checkpoint file, ignoring a trailing record cut short by a crash
+fn read_checkpoint(path: &Path) -> PyResult<Checkpoint> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    let valid_length = contents.rfind('\n').map_or(0, |i| i + 1);
+    let mut lines = contents[..valid_length].lines();
+
+    if lines.next() != Some(MAGIC) {
+        return Err(CheckpointError::InvalidHeader.into());
+    }
+    let key = lines
+        .next()
+        .and_then(|line| line.strip_prefix("key "))
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .ok_or(CheckpointError::InvalidHeader)?;
+    let images = parse_field(lines.next(), "permutation")?;
+    let flips = parse_field(lines.next(), "flips")?;
+    let transverse_flip = match parse_field::<usize>(lines.next(), "transverse_flip")?[..] {
+        [flag] if flag <= 1 => flag == 1,
+        _ => return Err(CheckpointError::InvalidHeader.into()),
+    };
+    let complexity = match parse_field(lines.next(), "complexity")?[..] {
+        [complexity] => complexity,
+        _ => return Err(CheckpointError::InvalidHeader.into()),
+    };
+    let perm = SignedPermutation::new(images, flips, transverse_flip)?;
+    let expected = checkpoint_key(&perm, complexity);
+    if key != expected {
+        return Err(CheckpointError::KeyMismatch {
+            expected,
+            found: key,
+        }
+        .into());
+    }
+
+    let mut results = Vec::new();
+    for (line_number, line) in lines.enumerate() {
+        let result = parse_record(line).ok_or(CheckpointError::InvalidRecord(line_number + 7))?;
+        results.push(result);
+    }
+
+    Ok(Checkpoint {
+        perm,
+        complexity,
+        results,
+        valid_length: valid_length as u64,
+    })
+}
+
+/// Computes the pairs missing from `previous`, appending each batch of `checkpoint_interval`
+/// results to `file` as they complete
+fn continue_sweep(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    file: File,
+    checkpoint_interval: usize,
+    mut previous: Vec<SweepResult>,
+) -> PyResult<Vec<SweepResult>> {
+    check_sweep_bounds(perm, complexity)?;
+    let done: HashSet<(u64, u64)> = previous.iter().map(|(pair, _)| *pair).collect();
+    let checkpoint_interval = checkpoint_interval.max(1);
+
+    let computed = run_interruptible(py, |cancelled| {
+        let (sender, receiver) = mpsc::channel::<SweepResult>();
+        let written = thread::scope(|scope| {
+            let writer = scope.spawn(move || -> io::Result<Vec<SweepResult>> {
+                let mut file = BufWriter::new(file);
+                let mut computed = Vec::new();
+                let mut pending = 0;
+                for ((m, n), (two_sided, one_sided)) in receiver {
+                    writeln!(file, "{} {} {} {}", m, n, two_sided, one_sided)?;
+                    computed.push(((m, n), (two_sided, one_sided)));
+                    pending += 1;
+                    if pending >= checkpoint_interval {
+                        file.flush()?;
+                        pending = 0;
+                    }
+                }
+                file.flush()?;
+                Ok(computed)
+            });
+
+            coprime_pairs_upto(complexity)
+                .filter(|pair| !done.contains(pair))
+                .for_each_with(sender, |sender, (m, n)| {
+                    if !cancelled.load(Ordering::Relaxed) {
+                        let counts = cached_component_counts(perm, m, n, Direction::Left);
+                        // The writer only hangs up after an I/O error, which is reported below
+                        let _ = sender.send(((m, n), counts));
+                    }
+                });
+
+            writer
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        });
+        if cancelled.load(Ordering::Relaxed) {
+            None
+        } else {
+            Some(written)
+        }
+    })??;
+
+    previous.extend(computed);
+    previous.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    Ok(previous)
+}
+
+/// Drops any partial trailing record from the checkpoint file and continues its sweep
+fn resume_checkpoint(
+    py: Python,
+    path: &Path,
+    checkpoint: Checkpoint,
+    checkpoint_interval: usize,
+) -> PyResult<Vec<SweepResult>> {
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_len(checkpoint.valid_length)?;
+    let file = OpenOptions::new().append(true).open(path)?;
+    continue_sweep(
+        py,
+        &checkpoint.perm,
+        checkpoint.complexity,
+        file,
+        checkpoint_interval,
+        checkpoint.results,
+    )
+}
+
+/// Sweep over all coprime (m,n) pairs up to a complexity, checkpointing results to `path`.
+/// If `path` already holds a checkpoint of the same sweep, only the missing pairs are computed.
+#[pyfunction]
+#[pyo3(signature = (perm, complexity, path, checkpoint_interval = 1000))]
+fn checkpointed_sweep(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    path: &str,
+    checkpoint_interval: usize,
+) -> PyResult<Vec<SweepEntry>> {
+    let path = Path::new(path);
+    let results = if path.exists() {
+        let checkpoint = read_checkpoint(path)?;
+        let expected = checkpoint_key(perm, complexity);
+        let found = checkpoint_key(&checkpoint.perm, checkpoint.complexity);
+        if expected != found {
+            return Err(CheckpointError::KeyMismatch { expected, found }.into());
+        }
+        resume_checkpoint(py, path, checkpoint, checkpoint_interval)?
+    } else {
+        let mut file = File::create(path)?;
+        write_header(&mut file, perm, complexity)?;
+        continue_sweep(py, perm, complexity, file, checkpoint_interval, vec![])?
+    };
+    Ok(entries(results))
+}
+
+/// Resumes the sweep recorded in the checkpoint file at `path`, returning all of its results
+#[pyfunction]
+#[pyo3(signature = (path, checkpoint_interval = 1000))]
+fn resume_sweep(py: Python, path: &str, checkpoint_interval: usize) -> PyResult<Vec<SweepEntry>> {
+    let path = Path::new(path);
+    let checkpoint = read_checkpoint(path)?;
+    resume_checkpoint(py, path, checkpoint, checkpoint_interval).map(entries)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(checkpointed_sweep, m)?)?;
+    m.add_function(wrap_pyfunction!(resume_sweep, m)?)?;
+    m.add(
+        "CheckpointException",
+        m.py().get_type::<CheckpointException>(),
+    )?;
+    Ok(())
+}
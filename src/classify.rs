@@ -0,0 +1,125 @@
+//! Survey of all signed permutations of a given size
+//!
+//! Every signed permutation on `size` letters is swept over the same (m,n) pairs, each sweep
+//! running on a single thread while the permutations are spread over all threads. Permutations
+//! with identical sweeps are grouped together, which keeps the table small since many
+//! permutations resolve the same way.
+
+use crate::results::{entries, SweepEntry};
+use crate::symmetry::is_rotation_canonical;
+use crate::{
+    check_sweep_bounds, complexity_order, component_counts, run_interruptible, sweep_pairs_upto,
+    Direction, SignedPermutation, SlotLayout,
+};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+/// Component counts of one permutation for each pair of the sweep
+type Sweep = Vec<(u64, u64)>;
+
+/// Permutations of `0..size` in lexicographic order
+fn permutations(size: usize) -> Vec<Vec<usize>> {
+    let mut current: Vec<usize> = (0..size).collect();
+    let mut all = vec![current.clone()];
+    // Standard next permutation: find the last ascent, swap with the last larger entry, reverse
+    while let Some(ascent) = (1..size).rev().find(|i| current[i - 1] < current[*i]) {
+        let pivot = ascent - 1;
+        let larger = (ascent..size)
+            .rev()
+            .find(|i| current[*i] > current[pivot])
+            .unwrap();
+        current.swap(pivot, larger);
+        current[ascent..].reverse();
+        all.push(current.clone());
+    }
+    all
+}
+
+/// Every signed permutation on `size` letters, ordered by permutation and then by the bit mask
+/// of its flips
+fn signed_permutations(size: usize, transverse_flip: bool) -> Vec<SignedPermutation> {
+    permutations(size)
+        .into_iter()
+        .flat_map(|permutation| {
+            (0..1usize << size).map(move |mask| {
+                SignedPermutation::from_parts(
+                    permutation.clone(),
+                    (0..size).filter(|letter| mask >> letter & 1 == 1).collect(),
+                    transverse_flip,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Sweeps every signed permutation on `size` letters over the coprime (m,n) pairs of
+/// `count_components_upto_complexity`, in parallel over the permutations. Returns a list of
+/// classes `(permutations, results)` of permutations with identical sweeps, in the order of their
+/// first permutation. With `up_to_symmetry`, only the smallest of the rotations of the labels of
+/// each permutation is swept, since rotations give the same multicurves.
+#[pyfunction]
+#[pyo3(signature = (
+    size, complexity, up_to_symmetry = false, transverse_flip = false, direction = Direction::Left
+))]
+fn classify_all_permutations(
+    py: Python,
+    size: usize,
+    complexity: u64,
+    up_to_symmetry: bool,
+    transverse_flip: bool,
+    direction: Direction,
+) -> PyResult<Vec<(Vec<SignedPermutation>, Vec<SweepEntry>)>> {
+    let mut perms = signed_permutations(size, transverse_flip);
+    if let Some(perm) = perms.first() {
+        check_sweep_bounds(perm, complexity)?;
+    }
+    if up_to_symmetry {
+        perms.retain(is_rotation_canonical);
+    }
+    let mut pairs: Vec<(u64, u64)> = sweep_pairs_upto(complexity, false).collect();
+    pairs.sort_unstable_by_key(complexity_order);
+
+    let sweeps: Vec<Sweep> = run_interruptible(py, |cancelled| {
+        perms
+            .par_iter()
+            .map(|perm| {
+                pairs
+                    .iter()
+                    .map(|&(m, n)| {
+                        if cancelled.load(Ordering::Relaxed) {
+                            return None;
+                        }
+                        Some(component_counts(
+                            perm,
+                            &SlotLayout::Uniform(m),
+                            n,
+                            direction,
+                        ))
+                    })
+                    .collect()
+            })
+            .collect()
+    })?;
+
+    let mut classes: Vec<(Vec<SignedPermutation>, Sweep)> = Vec::new();
+    let mut class_of: HashMap<Sweep, usize> = HashMap::new();
+    for (perm, sweep) in perms.into_iter().zip(sweeps) {
+        let class = *class_of.entry(sweep.clone()).or_insert_with(|| {
+            classes.push((Vec::new(), sweep));
+            classes.len() - 1
+        });
+        classes[class].0.push(perm);
+    }
+    Ok(classes
+        .into_iter()
+        .map(|(perms, sweep)| (perms, entries(pairs.iter().copied().zip(sweep).collect())))
+        .collect())
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(classify_all_permutations, m)?)?;
+    Ok(())
+}
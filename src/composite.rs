@@ -0,0 +1,124 @@
+//! Configurations given by a word of signed permutations
+//!
+//! Stacking the bands of several signed permutations on the same letters, the copies leaving the
+//! top of a band go through each permutation of the word in turn before reaching the bottom. The
+//! copies of band j end up in the block of the composed permutation, reversed once for every flip
+//! met along the way, so the resolved multicurve is traced with the composed transition.
+
+use crate::results::ComponentCount;
+use crate::{
+    check_strand, component_counts, next_major_strand, Direction, Multiplicity, PyStrand,
+    SignedPermutation, SlotLayout,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Surgery along a word of signed permutations on the same letters, applied in order
+#[pyclass]
+#[derive(Debug, Clone)]
+struct CompositeConfiguration {
+    word: Vec<SignedPermutation>,
+    /// Single signed permutation with the same transitions as the word
+    composed: SignedPermutation,
+}
+
+/// Signed permutation applying `first` and then `second`, flipping the transverse strands if
+/// exactly one of them does
+fn compose(first: &SignedPermutation, second: &SignedPermutation) -> SignedPermutation {
+    let permutation = first
+        .permutation
+        .iter()
+        .map(|image| second.permutation[*image])
+        .collect();
+    let flip_set = (0..first.permutation.len())
+        .filter(|letter| {
+            first.flip_set.contains(letter) != second.flip_set.contains(&first.permutation[*letter])
+        })
+        .collect();
+    SignedPermutation::from_parts(
+        permutation,
+        flip_set,
+        first.transverse_flip != second.transverse_flip,
+    )
+}
+
+#[pymethods]
+impl CompositeConfiguration {
+    /// Takes a non-empty list of signed permutations of the same length
+    #[new]
+    fn new(word: Vec<SignedPermutation>) -> PyResult<Self> {
+        let first = word
+            .first()
+            .ok_or_else(|| PyValueError::new_err("The word must contain a signed permutation"))?;
+        if let Some(other) = word
+            .iter()
+            .find(|perm| perm.permutation.len() != first.permutation.len())
+        {
+            return Err(PyValueError::new_err(format!(
+                "All signed permutations of the word must have length {}, not {}",
+                first.permutation.len(),
+                other.permutation.len()
+            )));
+        }
+        let composed = word[1..]
+            .iter()
+            .fold(first.clone(), |composed, perm| compose(&composed, perm));
+        Ok(CompositeConfiguration { word, composed })
+    }
+
+    #[getter]
+    fn word(&self) -> Vec<SignedPermutation> {
+        self.word.clone()
+    }
+
+    /// Signed permutation with the same transitions as the whole word
+    fn composed(&self) -> SignedPermutation {
+        self.composed.clone()
+    }
+
+    /// Same as the module level `get_next_major_strand`, for the composed transition
+    #[pyo3(signature = (m, n, strand, direction = Direction::Left))]
+    fn get_next_major_strand(
+        &self,
+        m: Multiplicity,
+        n: u64,
+        strand: PyStrand,
+        direction: Direction,
+    ) -> PyResult<(PyStrand, usize)> {
+        let layout = SlotLayout::new(&self.composed, m, n)?;
+        check_strand(&self.composed, &layout, n, strand)?;
+        Ok(next_major_strand(
+            &self.composed,
+            &layout,
+            n,
+            strand,
+            direction,
+        ))
+    }
+
+    /// Same as the module level `count_components_with_orientability`, for the composed transition
+    #[pyo3(signature = (m, n, direction = Direction::Left))]
+    fn count_components_with_orientability(
+        &self,
+        m: Multiplicity,
+        n: u64,
+        direction: Direction,
+    ) -> PyResult<ComponentCount> {
+        let layout = SlotLayout::new(&self.composed, m, n)?;
+        Ok(component_counts(&self.composed, &layout, n, direction).into())
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        let word: Vec<String> = self
+            .word
+            .iter()
+            .map(|perm| perm.__repr__())
+            .collect::<PyResult<_>>()?;
+        Ok(format!("CompositeConfiguration([{}])", word.join(", ")))
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<CompositeConfiguration>()?;
+    Ok(())
+}
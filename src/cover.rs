@@ -0,0 +1,83 @@
+//! Orientation double cover of a signed permutation
+//!
+//! A signed permutation acts on the signed letters ±j, sending +j to ±perm(j) and -j to the
+//! opposite sign, the sign changing exactly when j is flipped. This action is an unsigned
+//! permutation of 2 * len letters commuting with the involution -j <-> +j, and describes the bands
+//! of the orientation double cover of the regular neighborhood of the original curves.
+
+use crate::{next_major_strand, Direction, Multiplicity, SignedPermutation, SlotLayout};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::HashSet;
+
+/// Lifts the flips of `perm`, returning the unsigned permutation on 2 * len letters, where
+/// letter j stands for +j and letter len + j for -j, together with the covering involution. Raises
+/// ValueError if the transverse curve is twisted.
+#[pyfunction]
+fn orientation_double_cover(perm: &SignedPermutation) -> PyResult<(SignedPermutation, Vec<usize>)> {
+    if perm.transverse_flip {
+        return Err(PyValueError::new_err(
+            "The orientation double cover requires an untwisted transverse curve",
+        ));
+    }
+    let length = perm.permutation.len();
+    let mut images = vec![0; 2 * length];
+    for (letter, image) in perm.permutation.iter().enumerate() {
+        let sheet_change = if perm.flip_set.contains(&letter) {
+            length
+        } else {
+            0
+        };
+        for sheet in [0, length] {
+            // The constructor takes the inverse of the stored permutation
+            images[(image + sheet + sheet_change) % (2 * length)] = letter + sheet;
+        }
+    }
+    let involution = (0..2 * length)
+        .map(|letter| (letter + length) % (2 * length))
+        .collect();
+    Ok((SignedPermutation::new(images, vec![], false)?, involution))
+}
+
+/// Counts the components of the preimage of the resolved multicurve in the orientation double
+/// cover, by following each strand on both sheets. Every two-sided component lifts to two
+/// components and every one-sided component to a single one.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn double_cover_component_count(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<u64> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let mut visited = HashSet::new();
+    let mut components = 0;
+    for start in layout
+        .strands(perm, n)
+        .flat_map(|strand| [(strand, 0), (strand, 1)])
+    {
+        if visited.contains(&start) {
+            continue;
+        }
+        let (mut strand, mut sheet) = start;
+        loop {
+            visited.insert((strand, sheet));
+            let (next_strand, flipped) = next_major_strand(perm, &layout, n, strand, direction);
+            strand = next_strand;
+            sheet ^= flipped;
+            if (strand, sheet) == start {
+                break;
+            }
+        }
+        components += 1;
+    }
+    Ok(components)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(orientation_double_cover, m)?)?;
+    m.add_function(wrap_pyfunction!(double_cover_component_count, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,278 @@
+//! Drawings of the strand diagram
+//!
+//! The strands are placed around a circle in the order of `SlotLayout::strands`, transverse
+//! strands first, and every strand is joined by a chord to the next strand along its component.
+//! Each component gets its own color, and the chords of one-sided components are dashed.
+
+use crate::{
+    trace_components, Direction, Multiplicity, PyStrand, SignedPermutation, SlotLayout, Strand,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::fs;
+
+/// Color cycle for the components
+const COLORS: [&str; 10] = [
+    "1F77B4", "FF7F0E", "2CA02C", "D62728", "9467BD", "8C564B", "E377C2", "7F7F7F", "BCBD22",
+    "17BECF",
+];
+
+/// Strands are only labelled when there are at most this many
+const MAX_LABELS: usize = 64;
+
+/// Components as lists of positions around the circle in the order they are traversed, with
+/// their orientability
+struct Diagram {
+    points: usize,
+    labels: Vec<String>,
+    components: Vec<(Vec<usize>, usize)>,
+}
+
+impl Diagram {
+    fn new(perm: &SignedPermutation, layout: &SlotLayout, n: u64, direction: Direction) -> Self {
+        let strands: Vec<PyStrand> = layout.strands(perm, n).collect();
+        let positions: HashMap<PyStrand, usize> = strands
+            .iter()
+            .enumerate()
+            .map(|(position, strand)| (*strand, position))
+            .collect();
+        let labels = strands
+            .iter()
+            .map(|strand| match strand.strand {
+                Strand::Transverse(index) => format!("t{}", index),
+                Strand::PermutationDirection(band, copy) => format!("p{},{}", band, copy),
+            })
+            .collect();
+
+        let mut components = Vec::new();
+        trace_components(
+            perm,
+            layout,
+            n,
+            direction,
+            &mut Vec::new(),
+            |component, strand| component.push(positions[&strand]),
+            |component, _, orientability| {
+                components.push((std::mem::take(component), orientability))
+            },
+        );
+        Diagram {
+            points: strands.len(),
+            labels,
+            components,
+        }
+    }
+
+    /// Unit vector towards a position, going clockwise from the top
+    fn direction(&self, position: usize) -> (f64, f64) {
+        let angle = PI / 2.0 - 2.0 * PI * position as f64 / self.points as f64;
+        (angle.cos(), angle.sin())
+    }
+
+    /// Chords of each component, as pairs of consecutive positions along it
+    fn chords(component: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+        component
+            .iter()
+            .zip(component.iter().cycle().skip(1))
+            .map(|(from, to)| (*from, *to))
+    }
+
+    fn to_svg(&self) -> String {
+        let (center, radius) = (250.0, 200.0);
+        let point = |(x, y): (f64, f64), scale: f64| {
+            (center + scale * radius * x, center - scale * radius * y)
+        };
+        let height = 500 + 20 * self.components.len();
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="500" height="{0}" viewBox="0 0 500 {0}">"#,
+            height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r##"<circle cx="{0}" cy="{0}" r="{1}" fill="none" stroke="#CCCCCC"/>"##,
+            center, radius
+        )
+        .unwrap();
+
+        for (index, (component, orientability)) in self.components.iter().enumerate() {
+            let color = COLORS[index % COLORS.len()];
+            let dash = if *orientability == 1 {
+                r#" stroke-dasharray="6 4""#
+            } else {
+                ""
+            };
+            for (from, to) in Self::chords(component) {
+                let (x1, y1) = point(self.direction(from), 1.0);
+                let path = if from == to {
+                    // A strand that is its own successor is drawn as a small loop outside
+                    let (x2, y2) = point(self.direction(from), 1.08);
+                    format!(
+                        "M {:.2} {:.2} A 8 8 0 1 1 {:.2} {:.2} A 8 8 0 1 1 {:.2} {:.2}",
+                        x1, y1, x2, y2, x1, y1
+                    )
+                } else {
+                    let (x2, y2) = point(self.direction(to), 1.0);
+                    let (dx1, dy1) = self.direction(from);
+                    let (dx2, dy2) = self.direction(to);
+                    let (cx, cy) = point(((dx1 + dx2) * 0.2, (dy1 + dy2) * 0.2), 1.0);
+                    format!(
+                        "M {:.2} {:.2} Q {:.2} {:.2} {:.2} {:.2}",
+                        x1, y1, cx, cy, x2, y2
+                    )
+                };
+                writeln!(
+                    svg,
+                    r##"<path d="{}" fill="none" stroke="#{}" stroke-width="1.5"{}/>"##,
+                    path, color, dash
+                )
+                .unwrap();
+            }
+        }
+
+        for position in 0..self.points {
+            let (x, y) = point(self.direction(position), 1.0);
+            writeln!(
+                svg,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="3" fill="black"/>"#,
+                x, y
+            )
+            .unwrap();
+            if self.points <= MAX_LABELS {
+                let (x, y) = point(self.direction(position), 1.12);
+                writeln!(
+                    svg,
+                    r#"<text x="{:.2}" y="{:.2}" font-size="10" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+                    x, y, self.labels[position]
+                )
+                .unwrap();
+            }
+        }
+
+        for (index, (component, orientability)) in self.components.iter().enumerate() {
+            let sides = if *orientability == 1 {
+                "one-sided"
+            } else {
+                "two-sided"
+            };
+            writeln!(
+                svg,
+                r##"<text x="20" y="{}" font-size="12" fill="#{}">component {}: length {}, {}</text>"##,
+                505 + 20 * index,
+                COLORS[index % COLORS.len()],
+                index,
+                component.len(),
+                sides
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn to_tikz(&self) -> String {
+        let radius = 4.0;
+        let point = |(x, y): (f64, f64), scale: f64| (scale * radius * x, scale * radius * y);
+        let mut tikz = String::new();
+        for (index, color) in COLORS.iter().enumerate() {
+            writeln!(
+                tikz,
+                r"\definecolor{{component{}}}{{HTML}}{{{}}}",
+                index, color
+            )
+            .unwrap();
+        }
+        tikz.push_str("\\begin{tikzpicture}\n");
+        writeln!(tikz, r"\draw[gray!40] (0,0) circle ({});", radius).unwrap();
+
+        for (index, (component, orientability)) in self.components.iter().enumerate() {
+            let style = if *orientability == 1 {
+                format!("component{}, dashed", index % COLORS.len())
+            } else {
+                format!("component{}", index % COLORS.len())
+            };
+            for (from, to) in Self::chords(component) {
+                let (x1, y1) = point(self.direction(from), 1.0);
+                if from == to {
+                    let (x2, y2) = point(self.direction(from), 1.06);
+                    writeln!(
+                        tikz,
+                        r"\draw[{}] ({:.3},{:.3}) circle ({:.3});",
+                        style,
+                        (x1 + x2) / 2.0,
+                        (y1 + y2) / 2.0,
+                        0.03 * radius
+                    )
+                    .unwrap();
+                } else {
+                    let (x2, y2) = point(self.direction(to), 1.0);
+                    let (dx1, dy1) = self.direction(from);
+                    let (dx2, dy2) = self.direction(to);
+                    let (cx, cy) = point(((dx1 + dx2) * 0.2, (dy1 + dy2) * 0.2), 1.0);
+                    writeln!(
+                        tikz,
+                        r"\draw[{}] ({:.3},{:.3}) .. controls ({:.3},{:.3}) .. ({:.3},{:.3});",
+                        style, x1, y1, cx, cy, x2, y2
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        for position in 0..self.points {
+            let (x, y) = point(self.direction(position), 1.0);
+            writeln!(tikz, r"\fill ({:.3},{:.3}) circle (1.5pt);", x, y).unwrap();
+            if self.points <= MAX_LABELS {
+                let (x, y) = point(self.direction(position), 1.1);
+                writeln!(
+                    tikz,
+                    r"\node at ({:.3},{:.3}) {{\tiny {}}};",
+                    x, y, self.labels[position]
+                )
+                .unwrap();
+            }
+        }
+        tikz.push_str("\\end{tikzpicture}\n");
+        tikz
+    }
+}
+
+/// Draws the strands of the resolved multicurve around a circle, joining each strand to the next
+/// one along its component, with one color for each component and dashed one-sided components.
+/// Writes an SVG image, or a TikZ picture if `format` is `'tikz'`, to the file at `path`.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, path, format = "svg", direction = Direction::Left))]
+fn draw_configuration(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    path: &str,
+    format: &str,
+    direction: Direction,
+) -> PyResult<()> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let diagram = Diagram::new(perm, &layout, n, direction);
+    let contents = match format {
+        "svg" => diagram.to_svg(),
+        "tikz" => diagram.to_tikz(),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown format '{}': only 'svg' and 'tikz' allowed",
+                format
+            )))
+        }
+    };
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(draw_configuration, m)?)?;
+    Ok(())
+}
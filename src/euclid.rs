@@ -0,0 +1,214 @@
+//! Component counting by Rauzy induction
+//!
+//! When the transverse curve is two-sided, following a resolved multicurve from one band strand
+//! to the next jumps over the transverse strands, and the first return map on the `m * len` band
+//! strands is the signed block permutation of the bands followed by a rotation by `n` modulo
+//! `m * len`. This is an interval exchange with flips on integer lengths, and inducing it on ever
+//! shorter initial segments preserves its cycles and their orientability. Repeated moves with the
+//! same winner are done at once by division, as in the Euclidean algorithm on `n / m`.
+
+use crate::rauzy::move_loser;
+use crate::results::ComponentCount;
+use crate::{
+    component_counts, slope_parameters, Direction, Multiplicity, SignedPermutation, Slope,
+    SlotLayout,
+};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Interval exchange with flips on integer points, given by the labels of the intervals in order
+/// along the domain and along the image
+struct DiscreteExchange {
+    lengths: Vec<u64>,
+    flipped: Vec<bool>,
+    top: Vec<usize>,
+    bottom: Vec<usize>,
+}
+
+impl DiscreteExchange {
+    /// First return map on the band strands for m copies and n transverse strands, requiring
+    /// m * len > 0
+    fn first_return(perm: &SignedPermutation, m: u64, n: u64, direction: Direction) -> Self {
+        let slots = perm.permutation.len() as u64 * m;
+        let shift = match direction {
+            Direction::Left => n % slots,
+            Direction::Right => (slots - n % slots) % slots,
+        };
+        // Bottom positions from `cut` on wrap around to the start of the image
+        let cut = slots - shift;
+
+        let mut exchange = DiscreteExchange {
+            lengths: Vec::new(),
+            flipped: Vec::new(),
+            top: Vec::new(),
+            bottom: Vec::new(),
+        };
+        // Start of each label along the domain and along the image
+        let mut starts = Vec::new();
+        for (band, bottom_block) in perm.permutation.iter().enumerate() {
+            let flipped = perm.flipped[band];
+            let bottom_start = m * *bottom_block as u64;
+            let mut pieces = vec![(bottom_start, m)];
+            if bottom_start < cut && cut < bottom_start + m {
+                pieces = vec![
+                    (bottom_start, cut - bottom_start),
+                    (cut, bottom_start + m - cut),
+                ];
+            }
+            let mut top_start = m * band as u64;
+            // A flipped band sends the end of its domain to the start of its image
+            if flipped {
+                pieces.reverse();
+            }
+            for (piece_start, length) in pieces {
+                exchange.lengths.push(length);
+                exchange.flipped.push(flipped);
+                starts.push((top_start, (piece_start + shift) % slots));
+                top_start += length;
+            }
+        }
+
+        let mut labels: Vec<usize> = (0..starts.len()).collect();
+        labels.sort_by_key(|label| starts[*label].0);
+        exchange.top = labels.clone();
+        labels.sort_by_key(|label| starts[*label].1);
+        exchange.bottom = labels;
+        exchange
+    }
+
+    /// Numbers of two-sided and one-sided cycles, consuming the exchange
+    fn cycle_counts(mut self) -> (u64, u64) {
+        let (mut two_sided, mut one_sided) = (0, 0);
+        while let (Some(&alpha), Some(&beta)) = (self.top.last(), self.bottom.last()) {
+            if alpha == beta {
+                // The last interval is sent to itself, either identically or reversed
+                let length = self.lengths[alpha];
+                if self.flipped[alpha] {
+                    two_sided += length / 2;
+                    one_sided += length % 2;
+                } else {
+                    two_sided += length;
+                }
+                self.top.pop();
+                self.bottom.pop();
+            } else if self.lengths[alpha] == self.lengths[beta] {
+                // The interval beta is sent onto alpha, so it takes over the image of alpha
+                self.top.pop();
+                self.bottom.pop();
+                let position = self
+                    .bottom
+                    .iter()
+                    .position(|label| *label == alpha)
+                    .unwrap();
+                self.bottom[position] = beta;
+                self.flipped[beta] ^= self.flipped[alpha];
+            } else if self.lengths[alpha] > self.lengths[beta] {
+                Self::induce(
+                    &mut self.lengths,
+                    &mut self.flipped,
+                    &mut self.bottom,
+                    alpha,
+                );
+            } else {
+                Self::induce(&mut self.lengths, &mut self.flipped, &mut self.top, beta);
+            }
+        }
+        (two_sided, one_sided)
+    }
+
+    /// Rauzy move where the longer of the two last intervals, `winner`, absorbs the last interval
+    /// of the loser's `row`. An unflipped winner cycles through the labels after it in `row`, so
+    /// whole rounds of these moves are done at once.
+    fn induce(lengths: &mut [u64], flipped: &mut [bool], row: &mut Vec<usize>, winner: usize) {
+        let position = row.iter().position(|label| *label == winner).unwrap();
+        if !flipped[winner] {
+            let round: u64 = row[position + 1..]
+                .iter()
+                .map(|label| lengths[*label])
+                .sum();
+            let rounds = (lengths[winner] - 1) / round;
+            if rounds > 0 {
+                lengths[winner] -= rounds * round;
+                return;
+            }
+        }
+        let loser = move_loser(row, flipped, winner);
+        lengths[winner] -= lengths[loser];
+    }
+}
+
+/// Counts the two-sided and one-sided components like `count_components_with_orientability`, by
+/// Rauzy induction on the first return map to the band strands, taking a number of steps growing
+/// with the continued fraction expansion of the multiplicities instead of with `m * len + n`. Falls
+/// back to tracing the strands if the transverse curve is one-sided, the bands have different
+/// multiplicities or there are no band strands.
+#[pyfunction]
+#[pyo3(signature = (perm, m = None, n = None, direction = Direction::Left, *, slope = None))]
+fn count_components_fast(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Option<Multiplicity>,
+    n: Option<u64>,
+    direction: Direction,
+    slope: Option<Slope>,
+) -> PyResult<ComponentCount> {
+    let (m, n) = slope_parameters(m, n, slope)?;
+    let layout = SlotLayout::new(perm, m, n)?;
+    let counts = py.detach(|| match layout {
+        SlotLayout::Uniform(m) if !perm.transverse_flip && layout.slots(perm) > 0 => {
+            DiscreteExchange::first_return(perm, m, n, direction).cycle_counts()
+        }
+        layout => component_counts(perm, &layout, n, direction),
+    });
+    Ok(counts.into())
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(count_components_fast, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiscreteExchange;
+    use crate::{component_counts, Direction, SignedPermutation, SlotLayout};
+
+    /// Permutations of `0..length` in lexicographic order
+    fn permutations(length: usize) -> Vec<Vec<usize>> {
+        if length == 0 {
+            return vec![vec![]];
+        }
+        let mut result = Vec::new();
+        for smaller in permutations(length - 1) {
+            for position in 0..length {
+                let mut permutation = smaller.clone();
+                permutation.insert(position, length - 1);
+                result.push(permutation);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn rauzy_induction_matches_tracing() {
+        for length in 1..=4 {
+            for permutation in permutations(length) {
+                for mask in 0..1 << length {
+                    let flips = (0..length).filter(|j| mask & (1 << j) != 0).collect();
+                    let perm = SignedPermutation::from_parts(permutation.clone(), flips, false);
+                    for m in 1..=4 {
+                        for n in 0..=6 {
+                            for direction in [Direction::Left, Direction::Right] {
+                                let fast = DiscreteExchange::first_return(&perm, m, n, direction)
+                                    .cycle_counts();
+                                let traced =
+                                    component_counts(&perm, &SlotLayout::Uniform(m), n, direction);
+                                assert_eq!(fast, traced, "{:?} m={} n={}", perm, m, n);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Sweep over the slopes of the Stern–Brocot tree
+//!
+//! Every coprime pair (m, n) of positive integers appears exactly once in the Stern–Brocot tree,
+//! rooted at (1, 1). The children of a node are the mediants of its slope m/n with the closest
+//! slopes above and below it among its ancestors and the bounds 0/1 and 1/0, so that the
+//! neighbours of a slope in the Farey sequences are its ancestors.
+
+use crate::results::ComponentCount;
+use crate::{
+    component_counts, run_interruptible, strand_count, Direction, SignedPermutation, SlotLayout,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::sync::atomic::Ordering;
+
+/// Largest number of nodes `stern_brocot_sweep` builds, reached at depth 21
+const MAX_NODES: u64 = (1 << 22) - 1;
+
+/// A slope m/n of the Stern–Brocot tree with its component counts. `parent`, `left` and `right`
+/// are positions in the list of nodes, `left` being the child with the smaller slope.
+#[pyclass]
+#[derive(Debug, Clone, Copy)]
+struct SternBrocotNode {
+    #[pyo3(get)]
+    m: u64,
+    #[pyo3(get)]
+    n: u64,
+    #[pyo3(get)]
+    depth: u32,
+    #[pyo3(get)]
+    parent: Option<usize>,
+    #[pyo3(get)]
+    left: Option<usize>,
+    #[pyo3(get)]
+    right: Option<usize>,
+    #[pyo3(get)]
+    counts: ComponentCount,
+}
+
+#[pymethods]
+impl SternBrocotNode {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "SternBrocotNode(m={}, n={}, depth={}, counts=({}, {}))",
+            self.m, self.n, self.depth, self.counts.two_sided, self.counts.one_sided
+        ))
+    }
+}
+
+/// Nodes of the tree down to `depth` in breadth-first order, with counts still to be filled in
+fn tree(depth: u32) -> Vec<SternBrocotNode> {
+    let mut nodes = vec![SternBrocotNode {
+        m: 1,
+        n: 1,
+        depth: 0,
+        parent: None,
+        left: None,
+        right: None,
+        counts: (0, 0).into(),
+    }];
+    // Closest slopes below and above each node among its ancestors and the bounds
+    let mut bounds = vec![((0, 1), (1, 0))];
+    let mut index = 0;
+    while index < nodes.len() {
+        let node = nodes[index];
+        if node.depth < depth {
+            let (below, above) = bounds[index];
+            let children = [
+                (
+                    (below.0 + node.m, below.1 + node.n),
+                    (below, (node.m, node.n)),
+                ),
+                (
+                    (above.0 + node.m, above.1 + node.n),
+                    ((node.m, node.n), above),
+                ),
+            ];
+            for (child, ((m, n), child_bounds)) in children.iter().enumerate() {
+                if child == 0 {
+                    nodes[index].left = Some(nodes.len());
+                } else {
+                    nodes[index].right = Some(nodes.len());
+                }
+                nodes.push(SternBrocotNode {
+                    m: *m,
+                    n: *n,
+                    depth: node.depth + 1,
+                    parent: Some(index),
+                    ..node
+                });
+                bounds.push(*child_bounds);
+            }
+        }
+        index += 1;
+    }
+    nodes
+}
+
+/// Counts the components for every coprime (m, n) of the Stern–Brocot tree down to `depth`, the
+/// root (1, 1) being at depth 0. Returns the nodes in breadth-first order, so that node i has its
+/// children at positions 2i + 1 and 2i + 2.
+#[pyfunction]
+#[pyo3(signature = (perm, depth, direction = Direction::Left))]
+fn stern_brocot_sweep(
+    py: Python,
+    perm: &SignedPermutation,
+    depth: u32,
+    direction: Direction,
+) -> PyResult<Vec<SternBrocotNode>> {
+    // A tree of depth d has 2^(d + 1) - 1 nodes
+    let node_count = 1u64
+        .checked_shl(depth.saturating_add(1))
+        .unwrap_or(u64::MAX)
+        - 1;
+    if node_count > MAX_NODES {
+        return Err(PyValueError::new_err(format!(
+            "A Stern-Brocot tree of depth {} has {} nodes, more than the limit of {}",
+            depth, node_count, MAX_NODES
+        )));
+    }
+    let mut nodes = tree(depth);
+    for node in &nodes {
+        strand_count(perm, node.m, node.n)?;
+    }
+    let counts = run_interruptible(py, |cancelled| {
+        nodes
+            .par_iter()
+            .map(|node| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                Some(component_counts(
+                    perm,
+                    &SlotLayout::Uniform(node.m),
+                    node.n,
+                    direction,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+    })?;
+    for (node, counts) in nodes.iter_mut().zip(counts) {
+        node.counts = counts.into();
+    }
+    Ok(nodes)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<SternBrocotNode>()?;
+    m.add_function(wrap_pyfunction!(stern_brocot_sweep, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! C API of the core counting functions
+//!
+//! Built with the `ffi` feature, the library exports the functions below, declared in the header
+//! `include/counting_components.h` generated by cbindgen. Every fallible function returns a
+//! `CcStatus`, and on failure the message of the error can be read with `cc_last_error`.
+
+use crate::surgery::{
+    component_counts, strand_count, Direction, PermutationError, SignedPermutation, SlotLayout,
+};
+use crate::{complexity_order, sweep_pairs_upto, SweepResult};
+use rayon::prelude::*;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+
+/// Outcome of a call to the C API
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CcStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// Not a valid permutation on {0,1,...,n-1}
+    InvalidPermutation = 2,
+    /// Flip set not a subset of {0,1,...,n-1}
+    InvalidFlipset = 3,
+    /// The number of strands does not fit in 64 bits
+    Overflow = 4,
+}
+
+/// Which way a strand turns when resolving a crossing
+// Only ever constructed by the callers
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CcDirection {
+    Left = 0,
+    Right = 1,
+}
+
+impl From<CcDirection> for Direction {
+    fn from(direction: CcDirection) -> Self {
+        match direction {
+            CcDirection::Left => Direction::Left,
+            CcDirection::Right => Direction::Right,
+        }
+    }
+}
+
+/// An (m,n) pair of a sweep with its numbers of two-sided and one-sided components
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CcSweepEntry {
+    pub m: u64,
+    pub n: u64,
+    pub two_sided: u64,
+    pub one_sided: u64,
+}
+
+impl From<SweepResult> for CcSweepEntry {
+    fn from(((m, n), (two_sided, one_sided)): SweepResult) -> Self {
+        CcSweepEntry {
+            m,
+            n,
+            two_sided,
+            one_sided,
+        }
+    }
+}
+
+/// Opaque signed permutation, created by `cc_permutation_new` and freed by `cc_permutation_free`
+pub struct CcPermutation(SignedPermutation);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records `message` as the last error of the calling thread and returns `status`
+fn fail(status: CcStatus, message: impl ToString) -> CcStatus {
+    let message = CString::new(message.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+/// Slice of `length` elements at `data`, which may be null when `length` is 0
+unsafe fn input_slice<'a, T>(data: *const T, length: usize) -> Option<&'a [T]> {
+    if length == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, length))
+    }
+}
+
+/// Message of the last error on the calling thread, or null if there was none. The string is
+/// owned by the library and stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn cc_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Builds a signed permutation from the same data as the Python constructor, where
+/// `permutation[j]` is the letter sent to j, storing it in `*out`
+///
+/// # Safety
+/// `permutation` and `flips` must point to `length` and `flips_length` elements, and `out` must
+/// be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn cc_permutation_new(
+    permutation: *const usize,
+    length: usize,
+    flips: *const usize,
+    flips_length: usize,
+    transverse_flip: bool,
+    out: *mut *mut CcPermutation,
+) -> CcStatus {
+    let (permutation, flips) = match (
+        input_slice(permutation, length),
+        input_slice(flips, flips_length),
+    ) {
+        (Some(permutation), Some(flips)) if !out.is_null() => (permutation, flips),
+        _ => return fail(CcStatus::NullPointer, "Null pointer argument"),
+    };
+    match SignedPermutation::try_new(permutation.to_vec(), flips.to_vec(), transverse_flip) {
+        Ok(perm) => {
+            *out = Box::into_raw(Box::new(CcPermutation(perm)));
+            CcStatus::Ok
+        }
+        Err(err @ PermutationError::InvalidFlipset(_)) => fail(CcStatus::InvalidFlipset, err),
+        Err(err) => fail(CcStatus::InvalidPermutation, err),
+    }
+}
+
+/// Frees a permutation created by `cc_permutation_new`. Does nothing on null.
+///
+/// # Safety
+/// `perm` must be null or come from `cc_permutation_new`, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn cc_permutation_free(perm: *mut CcPermutation) {
+    if !perm.is_null() {
+        drop(Box::from_raw(perm));
+    }
+}
+
+/// Counts the two-sided and one-sided components of the multicurve resolved from m copies of
+/// every band and n transverse strands, like `count_components_with_orientability`
+///
+/// # Safety
+/// `perm` must come from `cc_permutation_new`, and `two_sided` and `one_sided` must be valid
+/// pointers.
+#[no_mangle]
+pub unsafe extern "C" fn cc_count_components(
+    perm: *const CcPermutation,
+    m: u64,
+    n: u64,
+    direction: CcDirection,
+    two_sided: *mut u64,
+    one_sided: *mut u64,
+) -> CcStatus {
+    if perm.is_null() || two_sided.is_null() || one_sided.is_null() {
+        return fail(CcStatus::NullPointer, "Null pointer argument");
+    }
+    let perm = &(*perm).0;
+    if let Err(err) = strand_count(perm, m, n) {
+        return fail(CcStatus::Overflow, err);
+    }
+    let counts = component_counts(perm, &SlotLayout::Uniform(m), n, direction.into());
+    *two_sided = counts.0;
+    *one_sided = counts.1;
+    CcStatus::Ok
+}
+
+/// Counts the components of every (m,n) pair with m+n < complexity in parallel, like
+/// `count_components_upto_complexity`, skipping those with gcd(m,n) > 1 unless
+/// `include_non_coprime` is set. Stores an array of `*length` entries sorted by m+n then n in
+/// `*entries`, to be freed with `cc_sweep_free`.
+///
+/// # Safety
+/// `perm` must come from `cc_permutation_new`, and `entries` and `length` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn cc_count_components_upto_complexity(
+    perm: *const CcPermutation,
+    complexity: u64,
+    direction: CcDirection,
+    include_non_coprime: bool,
+    entries: *mut *mut CcSweepEntry,
+    length: *mut usize,
+) -> CcStatus {
+    if perm.is_null() || entries.is_null() || length.is_null() {
+        return fail(CcStatus::NullPointer, "Null pointer argument");
+    }
+    let perm = &(*perm).0;
+    if let Err(err) = strand_count(perm, complexity, 0) {
+        return fail(CcStatus::Overflow, err);
+    }
+    let direction = direction.into();
+    let mut results: Vec<SweepResult> = sweep_pairs_upto(complexity, include_non_coprime)
+        .map(|(m, n)| {
+            (
+                (m, n),
+                component_counts(perm, &SlotLayout::Uniform(m), n, direction),
+            )
+        })
+        .collect();
+    results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    let sweep: Box<[CcSweepEntry]> = results.into_iter().map(CcSweepEntry::from).collect();
+    *length = sweep.len();
+    *entries = Box::into_raw(sweep) as *mut CcSweepEntry;
+    CcStatus::Ok
+}
+
+/// Frees the entries of a sweep returned by `cc_count_components_upto_complexity`
+///
+/// # Safety
+/// `entries` and `length` must be exactly as returned by the sweep, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn cc_sweep_free(entries: *mut CcSweepEntry, length: usize) {
+    if !entries.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            entries, length,
+        )));
+    }
+}
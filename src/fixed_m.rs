@@ -0,0 +1,183 @@
+//! Sweeps over n with a fixed number m of copies of each band
+//!
+//! For a fixed m the bands only depend on the permutation, so the bottom position reached by
+//! every copy is computed once. With no flip on the transverse curve, every component runs
+//! through the bands, and a run of transverse strands between two bands is determined by its
+//! first strand in constant time. Each n then only has to follow the first return map on the
+//! `m * len` band strands.
+
+use crate::results::{entries, SweepEntry};
+use crate::{
+    component_counts, narrow, run_interruptible, strand_count, Direction, SignedPermutation,
+    SlotLayout, SweepResult,
+};
+use gcd::Gcd;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::sync::atomic::Ordering;
+
+/// Bottom position reached by the copy leaving each top position, and whether it is flipped
+fn band_exits(perm: &SignedPermutation, m: u64) -> Vec<(u64, usize)> {
+    let layout = SlotLayout::Uniform(m);
+    let mut exits = Vec::with_capacity(layout.slots(perm) as usize);
+    for (band, _) in perm.permutation.iter().enumerate() {
+        for copy in 0..m {
+            if perm.flipped[band] {
+                exits.push((layout.bottom_position(perm, band, m - copy - 1), 1));
+            } else {
+                exits.push((layout.bottom_position(perm, band, copy), 0));
+            }
+        }
+    }
+    exits
+}
+
+/// Counts the components for n transverse strands by following the first return map on the band
+/// strands, for a permutation without transverse flip
+fn first_return_counts(exits: &[(u64, usize)], n: u64, direction: Direction) -> (u64, u64) {
+    let slots = exits.len() as u64;
+    // Top position reached from the bottom position `bottom`, possibly through transverse strands
+    let next = |bottom: u64| match direction {
+        Direction::Left if bottom + n < slots => bottom + n,
+        Direction::Right if bottom >= n => bottom - n,
+        Direction::Left => {
+            let index = slots - bottom - 1;
+            let last = index + slots * ((n - 1 - index) / slots);
+            n - last - 1
+        }
+        Direction::Right => {
+            let last = bottom + slots * ((n - 1 - bottom) / slots);
+            slots + last - n
+        }
+    };
+
+    let mut visited = vec![false; exits.len()];
+    let (mut two_sided, mut one_sided) = (0, 0);
+    for start in 0..exits.len() {
+        if visited[start] {
+            continue;
+        }
+        let mut position = start;
+        let mut orientability = 0;
+        while !visited[position] {
+            visited[position] = true;
+            let (bottom, flipped) = exits[position];
+            orientability ^= flipped;
+            position = next(bottom) as usize;
+        }
+        if orientability == 0 {
+            two_sided += 1;
+        } else {
+            one_sided += 1;
+        }
+    }
+    (two_sided, one_sided)
+}
+
+/// Function to count the components of (m,n) for a fixed m and all 0 < n <= n_max in parallel,
+/// skipping those with gcd(m,n) > 1 unless `include_non_coprime` is set. The band data is shared
+/// between all n, and each n only follows the band strands when the transverse curve is two-sided.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n_max, direction = Direction::Left, include_non_coprime = false))]
+fn sweep_fixed_m(
+    py: Python,
+    perm: &SignedPermutation,
+    m: u64,
+    n_max: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+) -> PyResult<Vec<SweepEntry>> {
+    strand_count(perm, m, n_max)?;
+    // The exits of the band strands are stored, so their number must fit in a usize
+    narrow(strand_count(perm, m, 0)?)?;
+    let exits = band_exits(perm, m);
+    // Without band strands, or with a one-sided transverse curve, components may avoid the bands
+    let amortized = !exits.is_empty() && !perm.transverse_flip;
+    run_interruptible(py, |cancelled| {
+        (1..=n_max)
+            .into_par_iter()
+            .filter(|n| include_non_coprime || m.gcd_binary(*n) == 1)
+            .map(|n| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let counts = if amortized {
+                    first_return_counts(&exits, n, direction)
+                } else {
+                    component_counts(perm, &SlotLayout::Uniform(m), n, direction)
+                };
+                Some(((m, n), counts))
+            })
+            .collect()
+    })
+    .map(entries)
+}
+
+/// Eventual period of a sequence indexed by n, as `(preperiod, period)`
+type Period = (u64, u64);
+
+/// Preperiod s and period p with the smallest s + p, then the smallest p, such that the sequence
+/// is periodic from position s on, provided the periodic part repeats at least twice
+fn eventual_period<T: PartialEq>(sequence: &[T]) -> Option<(usize, usize)> {
+    let length = sequence.len();
+    let mut best: Option<(usize, usize)> = None;
+    for period in 1..=length / 2 {
+        if let Some((preperiod, shortest)) = best {
+            if period >= preperiod + shortest {
+                break;
+            }
+        }
+        let mut preperiod = length - period;
+        while preperiod > 0 && sequence[preperiod - 1] == sequence[preperiod - 1 + period] {
+            preperiod -= 1;
+        }
+        let repeats = length - preperiod >= 2 * period;
+        let shorter = match best {
+            Some((best_preperiod, best_period)) => {
+                preperiod + period < best_preperiod + best_period
+            }
+            None => true,
+        };
+        if repeats && shorter {
+            best = Some((preperiod, period));
+        }
+    }
+    best
+}
+
+/// Counts the components of (m,n) for a fixed m and all 0 < n <= n_max, coprime or not, and looks
+/// for an eventual period in n of the connectedness and of the counts. Each period is given as
+/// `(preperiod, period)`, the sequence being periodic for n > preperiod.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n_max, direction = Direction::Left))]
+fn detect_period_fixed_m(
+    py: Python,
+    perm: &SignedPermutation,
+    m: u64,
+    n_max: u64,
+    direction: Direction,
+) -> PyResult<(Vec<SweepEntry>, Option<Period>, Option<Period>)> {
+    let entries = sweep_fixed_m(py, perm, m, n_max, direction, true)?;
+    let counts: Vec<(u64, u64)> = entries
+        .iter()
+        .map(|entry| SweepResult::from(*entry).1)
+        .collect();
+    let (connected, counts) = py.detach(|| {
+        let connected: Vec<bool> = counts
+            .iter()
+            .map(|(two_sided, one_sided)| two_sided + one_sided == 1)
+            .collect();
+        (eventual_period(&connected), eventual_period(&counts))
+    });
+    let widen = |period: Option<(usize, usize)>| {
+        period.map(|(preperiod, period)| (preperiod as u64, period as u64))
+    };
+    Ok((entries, widen(connected), widen(counts)))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(detect_period_fixed_m, m)?)?;
+    m.add_function(wrap_pyfunction!(sweep_fixed_m, m)?)?;
+    Ok(())
+}
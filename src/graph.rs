@@ -0,0 +1,80 @@
+//! Transition graph of the strands
+//!
+//! The vertices are the strands and there is an edge from every strand to the strand returned by
+//! `get_next_major_strand`, marked with whether the local orientation is flipped along it. The
+//! components of the resolved multicurve are the cycles of this graph.
+
+use crate::{next_major_strand, Direction, Multiplicity, PyStrand, SignedPermutation, SlotLayout};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Edge with its networkx attributes
+type Edge = (PyStrand, PyStrand, HashMap<&'static str, usize>);
+
+/// Every transition, transverse strands first
+fn transitions(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<Vec<(PyStrand, PyStrand, usize)>> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    Ok(layout
+        .strands(perm, n)
+        .map(|strand| {
+            let (next_strand, flipped) = next_major_strand(perm, &layout, n, strand, direction);
+            (strand, next_strand, flipped)
+        })
+        .collect())
+}
+
+/// Returns the transition graph as a list of edges `(strand, next_strand, {'flipped': 0 or 1})`,
+/// which `networkx.DiGraph` accepts directly
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn transition_graph_edges(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<Vec<Edge>> {
+    Ok(transitions(perm, m, n, direction)?
+        .into_iter()
+        .map(|(strand, next_strand, flipped)| {
+            let mut attributes = HashMap::new();
+            attributes.insert("flipped", flipped);
+            (strand, next_strand, attributes)
+        })
+        .collect())
+}
+
+/// Returns the transition graph in the DOT language, with the flipped edges dashed
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn transition_graph_dot(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<String> {
+    let mut dot = String::from("digraph transitions {\n");
+    for (strand, next_strand, flipped) in transitions(perm, m, n, direction)? {
+        let style = if flipped == 1 { ", style=dashed" } else { "" };
+        writeln!(
+            dot,
+            "    \"{:?}\" -> \"{:?}\" [flipped={}{}];",
+            strand.strand, next_strand.strand, flipped, style
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(transition_graph_edges, m)?)?;
+    m.add_function(wrap_pyfunction!(transition_graph_dot, m)?)?;
+    Ok(())
+}
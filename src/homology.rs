@@ -0,0 +1,68 @@
+//! Homology classes of the components of the resolved multicurve
+//!
+//! A regular neighborhood N of the original curves is an annulus around the transverse curve with
+//! one band for each letter of the permutation. The co-cores of the bands, together with an arc
+//! crossing the annulus where the transverse strands begin, cut N into a disk. Counting how often
+//! a closed curve crosses each of these arcs therefore gives its coordinates in H_1(N), in the
+//! basis consisting of one loop through each band followed by the transverse curve.
+
+use crate::{trace_components, Direction, Multiplicity, SignedPermutation, SlotLayout, Strand};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Outputs the mod 2 homology class of each component of the resolved multicurve as a bit vector
+/// of length len + 1, in the basis of the module documentation. This is `homology_matrix` reduced
+/// mod 2: bit j is the parity of the passes of the component through band j, and the last bit the
+/// parity of its transverse strands. The class of the whole multicurve is the sum of the rows.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn homology_class_mod2(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<Vec<Vec<usize>>> {
+    Ok(homology_matrix(perm, m, n, direction)?
+        .into_iter()
+        .map(|row| row.into_iter().map(|entry| (entry % 2) as usize).collect())
+        .collect())
+}
+
+/// Outputs the integer coordinates of each component of the resolved multicurve as the rows of
+/// a matrix with len + 1 columns. Entry j counts the passes of the component through band j, which
+/// always run from the top to the bottom of the band, and the last entry counts its transverse
+/// strands. Components are listed in the same order as `component_profile`, and the columns sum
+/// to the multiplicities of the bands followed by n.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn homology_matrix(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<Vec<Vec<u64>>> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let length = perm.permutation.len();
+    let mut rows = Vec::new();
+    let mut row = vec![0; length + 1];
+
+    trace_components(
+        perm,
+        &layout,
+        n,
+        direction,
+        &mut row,
+        |row, strand| match strand.strand {
+            Strand::PermutationDirection(perm_index, _) => row[perm_index] += 1,
+            Strand::Transverse(_) => row[length] += 1,
+        },
+        |row, _, _| rows.push(std::mem::replace(row, vec![0; length + 1])),
+    );
+    Ok(rows)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(homology_class_mod2, m)?)?;
+    m.add_function(wrap_pyfunction!(homology_matrix, m)?)?;
+    Ok(())
+}
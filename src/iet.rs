@@ -0,0 +1,332 @@
+//! Interval exchange transformations with flips
+//!
+//! The signed permutation gives the combinatorics: the j-th interval from the left on top is
+//! translated to position perm(j) on the bottom, and reversed if j is flipped. Intervals are
+//! closed on the left and open on the right, so the map is defined on [0, L), where L is the total
+//! length, except that a flipped interval sends its left endpoint to the right end of its image.
+//! When all lengths are integers or fractions the map is computed exactly in units of their common
+//! denominator, and otherwise in floating point.
+
+use crate::SignedPermutation;
+use gcd::Gcd;
+use pyo3::exceptions::{PyOverflowError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::IntoPyObjectExt;
+
+/// A point of the domain, exact points being fractions `numerator / denominator` of the unit
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Point {
+    Exact(u128, u128),
+    Float(f64),
+}
+
+fn overflow() -> PyErr {
+    PyOverflowError::new_err("Exact interval exchange arithmetic overflows")
+}
+
+fn mul(a: u128, b: u128) -> PyResult<u128> {
+    a.checked_mul(b).ok_or_else(overflow)
+}
+
+/// Exact point in lowest terms
+fn reduced(numerator: u128, denominator: u128) -> Point {
+    let common = numerator.gcd_binary(denominator).max(1);
+    Point::Exact(numerator / common, denominator / common)
+}
+
+/// Reads a non-negative integer or fraction as a pair of numerator and denominator
+fn extract_fraction(obj: &Bound<'_, PyAny>) -> Option<(u128, u128)> {
+    let numerator = obj.getattr("numerator").ok()?.extract().ok()?;
+    let denominator = obj.getattr("denominator").ok()?.extract().ok()?;
+    Some((numerator, denominator))
+}
+
+/// Python class to represent an interval exchange transformation with flips
+#[pyclass]
+#[derive(Debug, PartialEq, Clone)]
+struct IntervalExchange {
+    perm: SignedPermutation,
+    lengths: Vec<f64>,
+    /// Lengths as multiples of `1 / unit`, if they are all rational
+    exact: Option<(Vec<u128>, u128)>,
+}
+
+impl IntervalExchange {
+    /// Left endpoints of the top and bottom intervals of each label, in the given units
+    fn endpoints<T>(&self, lengths: &[T]) -> (Vec<T>, Vec<T>)
+    where
+        T: Copy + Default + std::ops::Add<Output = T>,
+    {
+        let length = lengths.len();
+        let mut top = Vec::with_capacity(length);
+        let mut offset = T::default();
+        for interval in lengths {
+            top.push(offset);
+            offset = offset + *interval;
+        }
+        let mut band_ending_at = vec![0; length];
+        for (label, position) in self.perm.permutation.iter().enumerate() {
+            band_ending_at[*position] = label;
+        }
+        let mut bottom = vec![T::default(); length];
+        let mut offset = T::default();
+        for label in band_ending_at {
+            bottom[label] = offset;
+            offset = offset + lengths[label];
+        }
+        (top, bottom)
+    }
+
+    fn total(&self) -> f64 {
+        self.lengths.iter().sum()
+    }
+
+    /// Reads a point of the domain, keeping it exact if possible
+    fn extract_point(&self, obj: &Bound<'_, PyAny>) -> PyResult<Point> {
+        let point = match (&self.exact, extract_fraction(obj)) {
+            (Some(_), Some((numerator, denominator))) => reduced(numerator, denominator),
+            _ => Point::Float(obj.extract()?),
+        };
+        let inside = match (point, &self.exact) {
+            (Point::Exact(numerator, denominator), Some((lengths, unit))) => {
+                let total: u128 = lengths.iter().sum();
+                mul(numerator, *unit)? < mul(total, denominator)?
+            }
+            (Point::Float(x), _) => 0.0 <= x && x < self.total(),
+            _ => unreachable!(),
+        };
+        if !inside {
+            return Err(PyValueError::new_err(
+                "Point lies outside the domain of the interval exchange",
+            ));
+        }
+        Ok(point)
+    }
+
+    /// Image of a point, or `None` if it leaves the domain at the right end
+    fn apply(&self, point: Point) -> PyResult<Option<Point>> {
+        match point {
+            Point::Exact(numerator, denominator) => {
+                let (lengths, unit) = self.exact.as_ref().unwrap();
+                let (top, bottom) = self.endpoints(lengths);
+                // Work in units of 1 / (denominator * unit), where every endpoint is an integer
+                let scaled = mul(numerator, *unit)?;
+                let mut label = 0;
+                for (j, start) in top.iter().enumerate().skip(1) {
+                    if mul(*start, denominator)? <= scaled {
+                        label = j;
+                    }
+                }
+                let image = if self.perm.flip_set.contains(&label) {
+                    mul(bottom[label] + top[label] + lengths[label], denominator)? - scaled
+                } else {
+                    mul(bottom[label], denominator)?
+                        .checked_add(scaled)
+                        .ok_or_else(overflow)?
+                        - mul(top[label], denominator)?
+                };
+                let total: u128 = lengths.iter().sum();
+                if image >= mul(total, denominator)? {
+                    return Ok(None);
+                }
+                Ok(Some(reduced(image, mul(denominator, *unit)?)))
+            }
+            Point::Float(x) => {
+                let (top, bottom) = self.endpoints(&self.lengths);
+                let label = (0..self.lengths.len())
+                    .rev()
+                    .find(|j| top[*j] <= x)
+                    .unwrap();
+                let image = if self.perm.flip_set.contains(&label) {
+                    bottom[label] + top[label] + self.lengths[label] - x
+                } else {
+                    bottom[label] + x - top[label]
+                };
+                if image >= self.total() {
+                    return Ok(None);
+                }
+                Ok(Some(Point::Float(image)))
+            }
+        }
+    }
+
+    fn point_to_py(&self, py: Python, point: Point) -> PyResult<Py<PyAny>> {
+        match point {
+            Point::Exact(numerator, denominator) => Ok(py
+                .import("fractions")?
+                .getattr("Fraction")?
+                .call1((numerator, denominator))?
+                .into()),
+            Point::Float(x) => x.into_py_any(py),
+        }
+    }
+}
+
+#[pymethods]
+impl IntervalExchange {
+    /// Builds the interval exchange where the interval j on top has length `lengths[j]`. The
+    /// lengths must be positive, and are kept exact if they are all integers or fractions.
+    #[new]
+    fn new(perm: SignedPermutation, lengths: Vec<Bound<'_, PyAny>>) -> PyResult<Self> {
+        if lengths.len() != perm.permutation.len() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} lengths, one for each interval, got {}",
+                perm.permutation.len(),
+                lengths.len()
+            )));
+        }
+        let floats = lengths
+            .iter()
+            .map(|length| length.extract())
+            .collect::<PyResult<Vec<f64>>>()?;
+        if !floats.iter().all(|length| *length > 0.0) {
+            return Err(PyValueError::new_err("Interval lengths must be positive"));
+        }
+
+        let fractions: Option<Vec<(u128, u128)>> = lengths
+            .iter()
+            .map(|length| extract_fraction(length))
+            .collect();
+        let exact = match fractions {
+            Some(fractions) => {
+                let mut unit: u128 = 1;
+                for (_, denominator) in &fractions {
+                    unit = (unit / unit.gcd_binary(*denominator))
+                        .checked_mul(*denominator)
+                        .ok_or_else(overflow)?;
+                }
+                let scaled = fractions
+                    .iter()
+                    .map(|(numerator, denominator)| {
+                        numerator
+                            .checked_mul(unit / denominator)
+                            .ok_or_else(overflow)
+                    })
+                    .collect::<PyResult<Vec<u128>>>()?;
+                scaled
+                    .iter()
+                    .try_fold(0u128, |total, length| total.checked_add(*length))
+                    .ok_or_else(overflow)?;
+                Some((scaled, unit))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            perm,
+            lengths: floats,
+            exact,
+        })
+    }
+
+    /// Lengths of the intervals as floats
+    #[getter]
+    fn lengths(&self) -> Vec<f64> {
+        self.lengths.clone()
+    }
+
+    /// Whether the map is computed exactly
+    #[getter]
+    fn is_exact(&self) -> bool {
+        self.exact.is_some()
+    }
+
+    /// Points between consecutive intervals on top, where the map is discontinuous
+    #[getter]
+    fn discontinuities(&self, py: Python) -> PyResult<Vec<Py<PyAny>>> {
+        let points: Vec<Point> = match &self.exact {
+            Some((lengths, unit)) => {
+                let (top, _) = self.endpoints(lengths);
+                top.into_iter()
+                    .skip(1)
+                    .map(|position| reduced(position, *unit))
+                    .collect()
+            }
+            None => {
+                let (top, _) = self.endpoints(&self.lengths);
+                top.into_iter().skip(1).map(Point::Float).collect()
+            }
+        };
+        points
+            .into_iter()
+            .map(|point| self.point_to_py(py, point))
+            .collect()
+    }
+
+    /// Evaluates the map at a point of [0, L). Raises `ValueError` if the point is outside the
+    /// domain or is the left endpoint of a flipped interval sent to L.
+    fn __call__(&self, py: Python, x: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        let point = self.extract_point(x)?;
+        match self.apply(point)? {
+            Some(image) => self.point_to_py(py, image),
+            None => Err(PyValueError::new_err(
+                "Point is sent to the right end of the domain",
+            )),
+        }
+    }
+
+    /// Returns the first `steps + 1` points of the orbit of `x`, starting with `x`. The orbit
+    /// stops early if it reaches the right end of the domain.
+    fn orbit(&self, py: Python, x: &Bound<'_, PyAny>, steps: usize) -> PyResult<Vec<Py<PyAny>>> {
+        let mut point = self.extract_point(x)?;
+        let mut orbit = vec![self.point_to_py(py, point)?];
+        for _ in 0..steps {
+            match self.apply(point)? {
+                Some(image) => point = image,
+                None => break,
+            }
+            orbit.push(self.point_to_py(py, point)?);
+        }
+        Ok(orbit)
+    }
+
+    /// Decides whether the map satisfies the Keane condition, i.e. has no connection, where a
+    /// discontinuity is sent onto a discontinuity or onto the right end of the domain. With exact
+    /// lengths the answer is exact: the orbit of a discontinuity stays in the finite lattice
+    /// spanned by the lengths, on which the map is injective, so it returns to its start or
+    /// reaches the right end, and the map has a connection as soon as there is a discontinuity.
+    /// With floating point lengths, discontinuities are compared up to `tolerance`, and `False` is
+    /// returned if a connection is found within `max_steps` iterates, and `None` otherwise, since
+    /// the search is inconclusive.
+    #[pyo3(signature = (max_steps = 1000, tolerance = 1e-9))]
+    fn satisfies_keane(&self, max_steps: usize, tolerance: f64) -> PyResult<Option<bool>> {
+        if self.lengths.len() < 2 {
+            return Ok(Some(true));
+        }
+        if self.exact.is_some() {
+            return Ok(Some(false));
+        }
+        let (top, _) = self.endpoints(&self.lengths);
+        let starts = &top[1..];
+        let is_discontinuity = |x: f64| starts.iter().any(|start| (x - start).abs() <= tolerance);
+
+        for start in starts {
+            let mut point = Point::Float(*start);
+            for _ in 0..max_steps {
+                point = match self.apply(point)? {
+                    Some(image) => image,
+                    None => return Ok(Some(false)),
+                };
+                if let Point::Float(x) = point {
+                    if is_discontinuity(x) {
+                        return Ok(Some(false));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "IntervalExchange({}, {:?})",
+            self.perm.__repr__()?,
+            self.lengths
+        ))
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<IntervalExchange>()?;
+    Ok(())
+}
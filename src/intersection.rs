@@ -0,0 +1,73 @@
+//! Intersections of the resolved multicurve with the original curves
+//!
+//! Every permutation direction strand runs once through a band and then crosses the annulus
+//! around the transverse curve γ, so each component meets γ once per permutation direction strand.
+//! Crossing the annulus moves a strand n slots along the circle of `m * len + n` slots, where the
+//! last n slots hold the transverse strands. Pushing the permutation direction curve δ off to the
+//! left of each block of m slots, the strand crosses δ once for every block start it moves past.
+
+use crate::{
+    narrow, strand_count, trace_components, Direction, SignedPermutation, SlotLayout, Strand,
+};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Number of positions `t` in `[0, x]` whose image modulo `slots + n` is the start of one of the
+/// `length` blocks of m slots
+fn block_starts_upto(x: u64, length: u64, m: u64, n: u64) -> u64 {
+    let circumference = length * m + n;
+    let remainder = x % circumference;
+    (x / circumference) * length + (remainder / m + 1).min(length)
+}
+
+/// Outputs, for each component of the resolved multicurve, the number of times it crosses the
+/// transverse curve and the permutation direction curve. Unless the transverse curve is flipped,
+/// all crossings with it happen in the same direction, so the first count is a geometric
+/// intersection number.
+/// Components are listed in the same order as `component_profile`.
+#[pyfunction]
+fn intersection_numbers(perm: &SignedPermutation, m: u64, n: u64) -> PyResult<Vec<(u64, u64)>> {
+    strand_count(perm, m, n)?;
+    let length = perm.permutation.len() as u64;
+    let slots = length * m;
+    let mut intersections = Vec::new();
+
+    if m == 0 {
+        // The multicurve consists of n copies of the transverse curve
+        intersections.resize(narrow(n)?, (0, length));
+        return Ok(intersections);
+    }
+
+    let mut counts = (0, 0);
+    trace_components(
+        perm,
+        &SlotLayout::Uniform(m),
+        n,
+        Direction::Left,
+        &mut counts,
+        |(with_transverse, with_permutation), strand| {
+            let position = match strand.strand {
+                Strand::PermutationDirection(perm_index, copy_index) => {
+                    *with_transverse += 1;
+                    let copy_index = if perm.flipped[perm_index] {
+                        m - copy_index - 1
+                    } else {
+                        copy_index
+                    };
+                    m * perm.permutation[perm_index] as u64 + copy_index
+                }
+                Strand::Transverse(index) if perm.transverse_flip => slots + index,
+                Strand::Transverse(index) => slots + n - index - 1,
+            };
+            *with_permutation += block_starts_upto(position + n, length, m, n)
+                - block_starts_upto(position, length, m, n);
+        },
+        |counts, _, _| intersections.push(std::mem::take(counts)),
+    );
+    Ok(intersections)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(intersection_numbers, m)?)?;
+    Ok(())
+}
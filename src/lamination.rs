@@ -0,0 +1,83 @@
+//! Export of the resolved multicurve to curver and flipper
+//!
+//! Puncturing every region of the surface cut along γ ∪ δ, the edges dual to the arcs of γ and
+//! to the bands of δ cut it into one quadrilateral around each intersection point. Splitting each
+//! quadrilateral along the diagonal parallel to the resolved strands gives an ideal
+//! triangulation, on which the resolved multicurve is in normal position: it crosses the edge
+//! dual to a band m times, the edge dual to an arc of γ n times, and each diagonal |m - n| times,
+//! once for every strand running between the two opposite sides it separates.
+
+use crate::{Direction, SignedPermutation};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Label of the reversed edge, as written `~edge` in curver and flipper
+fn reversed(edge: i64) -> i64 {
+    !edge
+}
+
+/// Outputs an ideal triangulation of the punctured surface as a list of triangles, each given by
+/// its three edge labels in anticlockwise order, with `~e` standing for edge e traversed
+/// backwards, together with the weights of the resolved multicurve on each edge. Edge j is dual to
+/// band j, edge k + b to the arc of γ after block b, and edge 2k + b is the diagonal of block b.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn to_curver_lamination(
+    perm: &SignedPermutation,
+    m: u64,
+    n: u64,
+    direction: Direction,
+) -> PyResult<(Vec<[i64; 3]>, Vec<u64>)> {
+    let length = perm.permutation.len();
+    if length == 0 {
+        return Err(PyValueError::new_err(
+            "The curves must intersect to triangulate the surface",
+        ));
+    }
+    if !perm.flip_set.is_empty() || perm.transverse_flip {
+        return Err(PyValueError::new_err(
+            "curver and flipper laminations require an orientable surface without flips",
+        ));
+    }
+
+    let mut band_ending_at = vec![0; length];
+    for (band, bottom) in perm.permutation.iter().enumerate() {
+        band_ending_at[*bottom] = band;
+    }
+    let band_edge = |band: usize| band as i64;
+    let arc_edge = |block: usize| (length + block) as i64;
+    let diagonal = |block: usize| (2 * length + block) as i64;
+
+    let mut triangles = Vec::with_capacity(2 * length);
+    for (block, arriving) in band_ending_at.iter().enumerate() {
+        // Sides of the quadrilateral, traversed anticlockwise from its bottom left corner. The
+        // band edges run from left to right and the arc edges from bottom to top.
+        let south = band_edge(*arriving);
+        let east = arc_edge(block);
+        let north = reversed(band_edge(block));
+        let west = reversed(arc_edge((block + length - 1) % length));
+        // The diagonal runs from bottom left to top right when turning left, and from bottom
+        // right to top left when turning right
+        match direction {
+            Direction::Left => {
+                triangles.push([south, east, reversed(diagonal(block))]);
+                triangles.push([diagonal(block), north, west]);
+            }
+            Direction::Right => {
+                triangles.push([south, diagonal(block), west]);
+                triangles.push([east, north, reversed(diagonal(block))]);
+            }
+        }
+    }
+
+    let mut weights = vec![m; length];
+    weights.extend(vec![n; length]);
+    weights.extend(vec![m.max(n) - m.min(n); length]);
+    Ok((triangles, weights))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(to_curver_lamination, m)?)?;
+    Ok(())
+}
@@ -1,86 +1,251 @@
 //! Python module written in Rust to resolve via surgery an intersection of curves
 
-#![feature(map_first_last)]
-
+#[cfg(feature = "python")]
+use cache::cached_component_counts;
+#[cfg(any(feature = "python", feature = "ffi"))]
 use gcd::Gcd;
+#[cfg(feature = "python")]
+use monte_carlo::SplitMix64;
+#[cfg(feature = "python")]
+use pyo3::basic::CompareOp;
+#[cfg(feature = "python")]
 use pyo3::create_exception;
-use pyo3::exceptions::PyException;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{
+    PyException, PyIndexError, PyKeyError, PyKeyboardInterrupt, PyOverflowError, PyRuntimeError,
+    PyTypeError, PyValueError,
+};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use pyo3::PyObjectProtocol;
+#[cfg(feature = "python")]
+use pyo3::pybacked::PyBackedStr;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyDict, PyString};
+#[cfg(feature = "python")]
+use pyo3::IntoPyObjectExt;
+#[cfg(feature = "python")]
+use rayon::iter::Either;
+#[cfg(any(feature = "python", feature = "ffi"))]
 use rayon::prelude::*;
-use std::collections::{BTreeSet, HashSet};
+#[cfg(feature = "python")]
+use results::{entries, extract_results, ComponentCount, Connectivity, SweepEntry};
+#[cfg(feature = "python")]
+use results_file::{read_results_file, write_results_file};
+#[cfg(feature = "python")]
+use std::collections::hash_map::DefaultHasher;
+#[cfg(feature = "python")]
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+#[cfg(feature = "python")]
+use std::convert::{Infallible, TryFrom};
+#[cfg(feature = "python")]
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "python")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "python")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "python")]
+use std::thread;
+#[cfg(feature = "python")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "python")]
+use surgery::{
+    component_counts, component_counts_bounded, component_total, connected_orientability,
+    next_major_strand, orbit_connectivity, previous_major_strand, strand_count, trace_components,
+    trace_components_bounded, Direction, LayoutError, Multiplicity, Offending, PermutationError,
+    PyStrand, SignedPermutation, SlotLayout, StepLimitExceeded, Strand, VisitedStrands, DEBUG,
+};
+
+#[cfg(feature = "python")]
+mod background;
+#[cfg(feature = "python")]
+mod bigons;
+#[cfg(feature = "python")]
+mod cache;
+#[cfg(feature = "python")]
+mod checkpoint;
+#[cfg(feature = "python")]
+mod classify;
+#[cfg(feature = "python")]
+mod composite;
+#[cfg(feature = "python")]
+mod cover;
+#[cfg(feature = "python")]
+mod draw;
+#[cfg(feature = "python")]
+mod euclid;
+#[cfg(feature = "python")]
+mod farey;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "python")]
+mod fixed_m;
+#[cfg(feature = "python")]
+mod graph;
+#[cfg(feature = "python")]
+mod homology;
+#[cfg(feature = "python")]
+mod iet;
+#[cfg(feature = "python")]
+mod intersection;
+#[cfg(feature = "python")]
+mod lamination;
+#[cfg(feature = "python")]
+mod monte_carlo;
+#[cfg(feature = "python")]
+mod oriented;
+#[cfg(feature = "python")]
+mod partial;
+#[cfg(feature = "python")]
+mod rauzy;
+#[cfg(feature = "python")]
+mod regions;
+#[cfg(feature = "python")]
+mod results;
+#[cfg(feature = "python")]
+mod results_file;
+#[cfg(feature = "python")]
+mod sqlite;
+mod surgery;
+#[cfg(feature = "python")]
+mod symmetry;
+#[cfg(feature = "python")]
+mod train_track;
+#[cfg(feature = "python")]
+mod twist;
+#[cfg(feature = "python")]
+mod two_permutation;
+#[cfg(feature = "python")]
+mod verify;
+#[cfg(feature = "wasm")]
+mod wasm;
 
+#[cfg(feature = "python")]
 create_exception!(counting_components, PermutationException, PyException);
+#[cfg(feature = "python")]
+create_exception!(
+    counting_components,
+    InvalidPermutationError,
+    PermutationException
+);
+#[cfg(feature = "python")]
+create_exception!(
+    counting_components,
+    InvalidFlipsetError,
+    PermutationException
+);
+#[cfg(feature = "python")]
+create_exception!(
+    counting_components,
+    InvalidStrandTypeError,
+    PermutationException
+);
+#[cfg(feature = "python")]
+create_exception!(counting_components, StepLimitExceededError, PyRuntimeError);
 
-/// Enum describing possible errors when creating a signed permutation or multiple strands
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum PermutationError {
-    /// Not a valid permutation on {0,1,...,n-1}
-    InvalidPermutation,
-    /// Flipset not a subset of {0,1,...,n-1}
-    InvalidFlipset,
-    /// Strand must be Transverse ('t') or a PermutationDirection ('p')
-    InvalidStrandType,
+#[cfg(feature = "python")]
+impl Offending {
+    /// Builds the exception of type `E` with the message of `err`, setting the known data as the
+    /// attributes `index`, `value` and `length`
+    fn to_err<E: pyo3::PyTypeInfo>(self, err: PermutationError) -> PyErr {
+        let err = PyErr::new::<E, _>(err.to_string());
+        Python::attach(|py| {
+            let instance = err.value(py);
+            for (name, attribute) in [
+                ("index", self.index),
+                ("value", self.value),
+                ("length", self.length),
+            ] {
+                // Exception instances always accept new attributes
+                let _ = instance.setattr(name, attribute);
+            }
+        });
+        err
+    }
 }
 
+#[cfg(feature = "python")]
 impl std::convert::From<PermutationError> for PyErr {
     fn from(err: PermutationError) -> PyErr {
         match err {
-            PermutationError::InvalidPermutation => {
-                PermutationException::new_err("Invalid permutation")
+            PermutationError::InvalidPermutation(offending) => {
+                offending.to_err::<InvalidPermutationError>(err)
             }
-            PermutationError::InvalidFlipset => PermutationException::new_err("Invalid flip set"),
-            PermutationError::InvalidStrandType => {
-                PermutationException::new_err("Invalid strand type: only 't' and 'p' allowed")
+            PermutationError::InvalidFlipset(offending) => {
+                offending.to_err::<InvalidFlipsetError>(err)
             }
+            PermutationError::InvalidStrandType(value) => {
+                let err = InvalidStrandTypeError::new_err(err.to_string());
+                Python::attach(|py| {
+                    let _ = err.value(py).setattr("value", value);
+                });
+                err
+            }
+            _ => PermutationException::new_err(err.to_string()),
         }
     }
 }
 
-/// Permutation and flip data
-#[pyclass]
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct SignedPermutation {
-    permutation: Vec<usize>,
-    flip_set: HashSet<usize>,
+#[cfg(feature = "python")]
+impl std::convert::From<LayoutError> for PyErr {
+    fn from(err: LayoutError) -> PyErr {
+        match err {
+            LayoutError::MultiplicityCount { .. } => PyValueError::new_err(err.to_string()),
+            _ => PyOverflowError::new_err(err.to_string()),
+        }
+    }
 }
 
-#[pymethods]
-impl SignedPermutation {
-    #[new]
-    #[args(flips = "vec![]")]
-    fn new(permutation: Vec<usize>, flips: Vec<usize>) -> PyResult<Self> {
-        let length = permutation.len();
-        let mut perm_vector = vec![length; length];
-        let mut flip_set = HashSet::new();
+#[cfg(feature = "python")]
+impl std::convert::From<StepLimitExceeded> for PyErr {
+    fn from(err: StepLimitExceeded) -> PyErr {
+        StepLimitExceededError::new_err(err.to_string())
+    }
+}
 
-        for (index, value) in permutation.into_iter().enumerate() {
-            if value >= length {
-                return Err(PermutationError::InvalidPermutation.into());
-            }
-            if perm_vector[value] != length {
-                return Err(PermutationError::InvalidPermutation.into());
+#[cfg(feature = "python")]
+impl SignedPermutation {
+    /// Builds the signed permutation sending each letter j to `images[j]`, flipped if j is in
+    /// `flips`
+    fn from_images(images: Vec<usize>, flips: Vec<usize>, transverse_flip: bool) -> PyResult<Self> {
+        let length = images.len();
+        let mut inverse = vec![length; length];
+        for (letter, image) in images.into_iter().enumerate() {
+            if image >= length || inverse[image] != length {
+                let offending = Offending::new(Some(letter), image, length);
+                return Err(PermutationError::InvalidPermutation(offending).into());
             }
-            perm_vector[value] = index;
+            inverse[image] = letter;
         }
+        Self::new(inverse, flips, transverse_flip)
+    }
 
-        for value in flips.into_iter() {
-            if value >= length {
-                return Err(PermutationError::InvalidFlipset.into());
+    /// Ends j + 1 of the prefixes {0, ..., j} sent to themselves, the last one being the length
+    fn prefix_ends(&self) -> Vec<usize> {
+        let mut ends = Vec::new();
+        let mut largest = 0;
+        for (letter, image) in self.permutation.iter().enumerate() {
+            largest = largest.max(*image);
+            if largest == letter {
+                ends.push(letter + 1);
             }
-            flip_set.insert(value);
         }
+        ends
+    }
+}
 
-        Ok(Self {
-            permutation: perm_vector,
-            flip_set,
-        })
+#[cfg(feature = "python")]
+#[pymethods]
+impl SignedPermutation {
+    #[new]
+    #[pyo3(signature = (permutation, flips = vec![], transverse_flip = false))]
+    fn new(permutation: Vec<usize>, flips: Vec<usize>, transverse_flip: bool) -> PyResult<Self> {
+        Ok(Self::try_new(permutation, flips, transverse_flip)?)
     }
 
-    #[call]
     fn __call__(&self, input: usize) -> PyResult<(usize, usize)> {
         if input >= self.permutation.len() {
-            return Err(PermutationError::InvalidPermutation.into());
+            let offending = Offending::new(None, input, self.permutation.len());
+            return Err(PermutationError::InvalidPermutation(offending).into());
         }
         if self.flip_set.contains(&input) {
             Ok((self.permutation[input], 1))
@@ -88,10 +253,645 @@ impl SignedPermutation {
             Ok((self.permutation[input], 0))
         }
     }
-}
 
-#[pyproto]
-impl PyObjectProtocol for SignedPermutation {
+    /// Applies the signed permutation to every index of a sequence or NumPy array, returning the
+    /// images and the signs as in `__call__`. NumPy arrays give back NumPy arrays, and other
+    /// inputs lists.
+    fn map_array(
+        &self,
+        py: Python,
+        indices: &Bound<'_, PyAny>,
+    ) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        let is_array = indices.hasattr("__array__")? && indices.hasattr("tolist")?;
+        let letters: Vec<usize> = if is_array {
+            indices.call_method0("tolist")?.extract()?
+        } else {
+            indices.extract()?
+        };
+        let length = self.permutation.len();
+        let mut images = Vec::with_capacity(letters.len());
+        let mut signs = Vec::with_capacity(letters.len());
+        for letter in letters {
+            if letter >= length {
+                let offending = Offending::new(None, letter, length);
+                return Err(PermutationError::InvalidPermutation(offending).into());
+            }
+            images.push(self.permutation[letter]);
+            signs.push(self.flipped[letter] as usize);
+        }
+        if is_array {
+            let numpy = py.import("numpy")?;
+            Ok((
+                numpy.call_method1("asarray", (images,))?.unbind(),
+                numpy.call_method1("asarray", (signs,))?.unbind(),
+            ))
+        } else {
+            Ok((images.into_py_any(py)?, signs.into_py_any(py)?))
+        }
+    }
+
+    /// Cycle decomposition of the permutation, each cycle starting at its smallest letter and
+    /// tagged with the product of the signs around it
+    fn cycles(&self) -> Vec<(Vec<usize>, isize)> {
+        let mut visited = vec![false; self.permutation.len()];
+        let mut cycles = Vec::new();
+        for start in 0..self.permutation.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut sign = 1;
+            let mut letter = start;
+            while !visited[letter] {
+                visited[letter] = true;
+                cycle.push(letter);
+                if self.flip_set.contains(&letter) {
+                    sign = -sign;
+                }
+                letter = self.permutation[letter];
+            }
+            cycles.push((cycle, sign));
+        }
+        cycles
+    }
+
+    /// Whether no proper prefix {0, ..., j} of the letters is sent to itself
+    fn is_irreducible(&self) -> bool {
+        self.prefix_ends().len() <= 1
+    }
+
+    /// Splits the permutation into irreducible blocks of consecutive letters, each sent to
+    /// itself, relabeled to start at 0 and keeping the transverse flip. An irreducible
+    /// permutation is its only block.
+    fn decompose(&self) -> Vec<Self> {
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        for end in self.prefix_ends() {
+            let permutation = self.permutation[start..end]
+                .iter()
+                .map(|image| image - start)
+                .collect();
+            let flip_set = (start..end)
+                .filter(|letter| self.flipped[*letter])
+                .map(|letter| letter - start)
+                .collect();
+            blocks.push(Self::from_parts(
+                permutation,
+                flip_set,
+                self.transverse_flip,
+            ));
+            start = end;
+        }
+        if blocks.is_empty() {
+            blocks.push(self.clone());
+        }
+        blocks
+    }
+
+    /// Parses signed cycle notation such as `"(0 2 -1)(3)"`, where each letter is sent to the
+    /// next one in its cycle, flipped if the next entry is negative. Letters that do not appear
+    /// are fixed.
+    #[staticmethod]
+    #[pyo3(signature = (cycles, transverse_flip = false))]
+    fn from_cycles(cycles: &str, transverse_flip: bool) -> PyResult<Self> {
+        let mut parsed: Vec<Vec<(usize, bool)>> = Vec::new();
+        let mut current: Option<Vec<(usize, bool)>> = None;
+        let mut rest = cycles.trim_start();
+        while !rest.is_empty() {
+            let invalid = || PyErr::from(PermutationError::InvalidCycleNotation);
+            match (rest.chars().next().unwrap(), &mut current) {
+                ('(', None) => {
+                    current = Some(Vec::new());
+                    rest = &rest[1..];
+                }
+                (')', Some(_)) => {
+                    parsed.push(current.take().unwrap());
+                    rest = &rest[1..];
+                }
+                (_, Some(cycle)) => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || c == ')')
+                        .unwrap_or(rest.len());
+                    let (token, remainder) = rest.split_at(end);
+                    let (flipped, digits) = match token.strip_prefix('-') {
+                        Some(digits) => (true, digits),
+                        None => (false, token),
+                    };
+                    if !digits.chars().all(|c| c.is_ascii_digit()) {
+                        return Err(invalid());
+                    }
+                    cycle.push((digits.parse().map_err(|_| invalid())?, flipped));
+                    rest = remainder;
+                }
+                _ => return Err(invalid()),
+            }
+            rest = rest.trim_start();
+        }
+        if current.is_some() {
+            return Err(PermutationError::InvalidCycleNotation.into());
+        }
+
+        let length = parsed
+            .iter()
+            .flatten()
+            .map(|(letter, _)| letter + 1)
+            .max()
+            .unwrap_or(0);
+        let mut images: Vec<Option<usize>> = vec![None; length];
+        let mut flips = Vec::new();
+        for cycle in &parsed {
+            for (i, (letter, _)) in cycle.iter().enumerate() {
+                let (image, flipped) = cycle[(i + 1) % cycle.len()];
+                if images[*letter].replace(image).is_some() {
+                    return Err(PermutationError::malformed_permutation().into());
+                }
+                if flipped {
+                    flips.push(*letter);
+                }
+            }
+        }
+        // The constructor takes the inverse of the stored permutation
+        let mut inverse = vec![0; length];
+        for (letter, image) in images.into_iter().enumerate() {
+            inverse[image.unwrap_or(letter)] = letter;
+        }
+        Self::new(inverse, flips, transverse_flip)
+    }
+
+    /// Constructs a signed permutation from the one-line list given to the constructor, with the
+    /// flipped letters marked by a minus sign. Since `-0` is not an integer, entries may also be
+    /// strings such as `"-0"`.
+    #[staticmethod]
+    #[pyo3(signature = (entries, transverse_flip = false))]
+    fn from_signed_list(entries: Vec<Bound<'_, PyAny>>, transverse_flip: bool) -> PyResult<Self> {
+        let mut permutation = Vec::with_capacity(entries.len());
+        let mut flips = Vec::new();
+        for entry in entries {
+            let (letter, flipped) = if let Ok(value) = entry.extract::<isize>() {
+                (value.unsigned_abs(), value < 0)
+            } else {
+                let text = entry.extract::<PyBackedStr>()?;
+                let text = text.trim();
+                let (flipped, digits) = match text.strip_prefix('-') {
+                    Some(digits) => (true, digits),
+                    None => (false, text),
+                };
+                if !digits.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(PermutationError::malformed_permutation().into());
+                }
+                let letter = digits
+                    .parse()
+                    .map_err(|_| PermutationError::malformed_permutation())?;
+                (letter, flipped)
+            };
+            permutation.push(letter);
+            if flipped {
+                flips.push(letter);
+            }
+        }
+        Self::new(permutation, flips, transverse_flip)
+    }
+
+    /// Writes the permutation in the signed cycle notation read by `from_cycles`
+    fn to_cycle_string(&self) -> String {
+        let mut s = String::new();
+        for (cycle, _) in self.cycles() {
+            s.push('(');
+            for (i, letter) in cycle.iter().enumerate() {
+                let previous = cycle[(i + cycle.len() - 1) % cycle.len()];
+                if i > 0 {
+                    s.push(' ');
+                }
+                if self.flip_set.contains(&previous) {
+                    s.push('-');
+                }
+                s.push_str(&letter.to_string());
+            }
+            s.push(')');
+        }
+        s
+    }
+
+    /// Writes the permutation as a SageMath `SignedPermutations` element in one-line notation on
+    /// the letters 1, ..., len, where letter j + 1 stands for j and the image of a flipped letter
+    /// is negative. The transverse flip is not part of the signed permutation and is dropped.
+    fn to_sage_string(&self) -> String {
+        let images: Vec<String> = self
+            .permutation
+            .iter()
+            .enumerate()
+            .map(|(letter, image)| {
+                if self.flip_set.contains(&letter) {
+                    format!("-{}", image + 1)
+                } else {
+                    (image + 1).to_string()
+                }
+            })
+            .collect();
+        format!(
+            "SignedPermutations({})([{}])",
+            self.permutation.len(),
+            images.join(", ")
+        )
+    }
+
+    /// Reads a signed permutation written by `to_sage_string`, or the bare one-line list such as
+    /// `"[2, -1, 3]"` printed by SageMath
+    #[staticmethod]
+    #[pyo3(signature = (sage, transverse_flip = false))]
+    fn from_sage_string(sage: &str, transverse_flip: bool) -> PyResult<Self> {
+        let mut text = sage.trim();
+        if let Some(rest) = text.strip_prefix("SignedPermutations(") {
+            let (length, rest) = rest
+                .split_once(")(")
+                .ok_or_else(PermutationError::malformed_permutation)?;
+            length
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| PermutationError::malformed_permutation())?;
+            text = rest
+                .strip_suffix(')')
+                .ok_or_else(PermutationError::malformed_permutation)?
+                .trim();
+        }
+        let entries = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .ok_or_else(PermutationError::malformed_permutation)?;
+
+        let mut images = Vec::new();
+        let mut flips = Vec::new();
+        for entry in entries.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let value: isize = entry
+                .parse()
+                .map_err(|_| PermutationError::malformed_permutation())?;
+            if value == 0 {
+                return Err(PermutationError::malformed_permutation().into());
+            }
+            if value < 0 {
+                flips.push(images.len());
+            }
+            images.push(value.unsigned_abs() - 1);
+        }
+        Self::from_images(images, flips, transverse_flip)
+    }
+
+    /// Writes the permutation in GAP cycle notation as a permutation of 1, ..., 2 * len, where
+    /// point j + 1 stands for the letter j and point len + j + 1 for its negative, as in
+    /// `orientation_double_cover`. The transverse flip is dropped.
+    fn to_gap_string(&self) -> String {
+        let length = self.permutation.len();
+        let mut images = vec![0; 2 * length];
+        for (letter, image) in self.permutation.iter().enumerate() {
+            let sheet_change = if self.flip_set.contains(&letter) {
+                length
+            } else {
+                0
+            };
+            images[letter] = image + sheet_change;
+            images[letter + length] = (image + length + sheet_change) % (2 * length);
+        }
+
+        let mut visited = vec![false; 2 * length];
+        let mut s = String::new();
+        for start in 0..2 * length {
+            if visited[start] || images[start] == start {
+                continue;
+            }
+            let mut points = Vec::new();
+            let mut point = start;
+            while !visited[point] {
+                visited[point] = true;
+                points.push((point + 1).to_string());
+                point = images[point];
+            }
+            s.push_str(&format!("({})", points.join(",")));
+        }
+        if s.is_empty() {
+            s.push_str("()");
+        }
+        s
+    }
+
+    /// Reads a signed permutation on `length` letters from GAP cycle notation in the convention
+    /// of `to_gap_string`. Raises `PermutationException` if the permutation does not commute with
+    /// exchanging j + 1 and len + j + 1.
+    #[staticmethod]
+    #[pyo3(signature = (gap, length, transverse_flip = false))]
+    fn from_gap_string(gap: &str, length: usize, transverse_flip: bool) -> PyResult<Self> {
+        let points = 2 * length;
+        let mut images: Vec<usize> = (0..points).collect();
+        let text: String = gap.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let (cycle, remainder) = rest
+                .strip_prefix('(')
+                .and_then(|rest| rest.split_once(')'))
+                .ok_or(PermutationError::InvalidCycleNotation)?;
+            rest = remainder;
+            if cycle.is_empty() {
+                continue;
+            }
+            let cycle = cycle
+                .split(',')
+                .map(|point| match point.parse::<usize>() {
+                    Ok(point) if 0 < point && point <= points => Ok(point - 1),
+                    _ => Err(PermutationError::InvalidCycleNotation),
+                })
+                .collect::<Result<Vec<usize>, _>>()?;
+            for (i, point) in cycle.iter().enumerate() {
+                if images[*point] != *point {
+                    return Err(PermutationError::InvalidCycleNotation.into());
+                }
+                images[*point] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+
+        let mut signed_images = Vec::with_capacity(length);
+        let mut flips = Vec::new();
+        for letter in 0..length {
+            let image = images[letter];
+            if images[letter + length] != (image + length) % points {
+                let offending = Offending {
+                    index: Some(letter),
+                    ..Offending::default()
+                };
+                return Err(PermutationError::InvalidPermutation(offending).into());
+            }
+            if image >= length {
+                flips.push(letter);
+            }
+            signed_images.push(image % length);
+        }
+        Self::from_images(signed_images, flips, transverse_flip)
+    }
+
+    /// Signed permutation matrix, with column j holding -1 or 1 in row `perm(j)` depending on
+    /// whether j is flipped, so that it sends the basis vector e_j to ±e_perm(j)
+    fn to_matrix(&self) -> Vec<Vec<i64>> {
+        let length = self.permutation.len();
+        let mut matrix = vec![vec![0; length]; length];
+        for (letter, image) in self.permutation.iter().enumerate() {
+            matrix[*image][letter] = if self.flip_set.contains(&letter) {
+                -1
+            } else {
+                1
+            };
+        }
+        matrix
+    }
+
+    /// Inverse of `to_matrix`, accepting any square matrix of integers such as a NumPy array
+    #[staticmethod]
+    #[pyo3(signature = (matrix, transverse_flip = false))]
+    fn from_matrix(matrix: Vec<Vec<i64>>, transverse_flip: bool) -> PyResult<Self> {
+        let length = matrix.len();
+        let mut images = vec![length; length];
+        let mut flips = Vec::new();
+        for (row, entries) in matrix.iter().enumerate() {
+            if entries.len() != length {
+                return Err(PermutationError::malformed_permutation().into());
+            }
+            for (letter, entry) in entries.iter().enumerate() {
+                match entry {
+                    0 => continue,
+                    1 | -1 if images[letter] == length => images[letter] = row,
+                    _ => return Err(PermutationError::malformed_permutation().into()),
+                }
+                if *entry == -1 {
+                    flips.push(letter);
+                }
+            }
+        }
+        // A missing column entry leaves the image out of range, and two nonzero entries in a row
+        // repeat it, both of which are rejected here
+        Self::from_images(images, flips, transverse_flip)
+    }
+
+    /// Linear chord diagram of the bands, on the 2k endpoints given by the top of the annulus
+    /// followed by its bottom, both from left to right. Band j is the chord `(j, k + perm(j),
+    /// sign)`, with sign -1 if j is flipped and 1 otherwise.
+    fn to_chord_diagram(&self) -> Vec<(usize, usize, i64)> {
+        let length = self.permutation.len();
+        self.permutation
+            .iter()
+            .enumerate()
+            .map(|(letter, image)| {
+                let sign = if self.flip_set.contains(&letter) {
+                    -1
+                } else {
+                    1
+                };
+                (letter, length + image, sign)
+            })
+            .collect()
+    }
+
+    /// Inverse of `to_chord_diagram`, accepting the chords in any order and with their endpoints
+    /// in either order. Every chord must join the top to the bottom of the annulus.
+    #[staticmethod]
+    #[pyo3(signature = (chords, transverse_flip = false))]
+    fn from_chord_diagram(
+        chords: Vec<(usize, usize, i64)>,
+        transverse_flip: bool,
+    ) -> PyResult<Self> {
+        let length = chords.len();
+        let mut images = vec![length; length];
+        let mut flips = Vec::new();
+        for (first, second, sign) in chords {
+            let (top, bottom) = (first.min(second), first.max(second));
+            // Both endpoints on the same row leave either top or bottom out of its row
+            if top >= length || bottom < length || bottom >= 2 * length || images[top] != length {
+                return Err(PermutationError::malformed_permutation().into());
+            }
+            images[top] = bottom - length;
+            match sign {
+                1 => {}
+                -1 => flips.push(top),
+                _ => return Err(PermutationError::malformed_permutation().into()),
+            }
+        }
+        // Two chords ending at the same bottom endpoint repeat an image, which is rejected here
+        Self::from_images(images, flips, transverse_flip)
+    }
+
+    /// Conjugates by the unsigned permutation sending each letter j to `relabeling[j]`, so that
+    /// j -> ±perm(j) becomes relabeling[j] -> ±relabeling[perm(j)]
+    fn conjugate(&self, relabeling: Vec<usize>) -> PyResult<Self> {
+        let length = self.permutation.len();
+        if relabeling.len() != length {
+            return Err(PermutationError::malformed_permutation().into());
+        }
+        let mut seen = vec![false; length];
+        for (index, label) in relabeling.iter().enumerate() {
+            if *label >= length || seen[*label] {
+                let offending = Offending::new(Some(index), *label, length);
+                return Err(PermutationError::InvalidPermutation(offending).into());
+            }
+            seen[*label] = true;
+        }
+        let mut images = vec![0; length];
+        for (letter, image) in self.permutation.iter().enumerate() {
+            images[relabeling[letter]] = relabeling[*image];
+        }
+        let flips = self.flip_set.iter().map(|letter| relabeling[*letter]);
+        Self::from_images(images, flips.collect(), self.transverse_flip)
+    }
+
+    /// Representative of the conjugacy class under relabeling. Relabeling preserves the cycles
+    /// and the pattern of flips around each of them, so each cycle is rotated to start its flip
+    /// pattern at the lexicographically smallest rotation, the cycles are sorted by length and
+    /// pattern, and then numbered consecutively.
+    fn canonical_form(&self) -> PyResult<Self> {
+        let mut patterns: Vec<Vec<bool>> = self
+            .cycles()
+            .into_iter()
+            .map(|(cycle, _)| {
+                let pattern: Vec<bool> = cycle
+                    .iter()
+                    .map(|letter| self.flip_set.contains(letter))
+                    .collect();
+                (0..pattern.len())
+                    .map(|shift| [&pattern[shift..], &pattern[..shift]].concat())
+                    .min()
+                    .unwrap()
+            })
+            .collect();
+        patterns.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+        let mut images = Vec::with_capacity(self.permutation.len());
+        let mut flips = Vec::new();
+        for pattern in patterns {
+            let start = images.len();
+            for (offset, flipped) in pattern.iter().enumerate() {
+                images.push(start + (offset + 1) % pattern.len());
+                if *flipped {
+                    flips.push(start + offset);
+                }
+            }
+        }
+        Self::from_images(images, flips, self.transverse_flip)
+    }
+
+    /// Extends the signed permutation by k fixed, unflipped letters after the existing ones
+    fn pad(&self, k: usize) -> Self {
+        let length = self.permutation.len();
+        let mut permutation = self.permutation.clone();
+        permutation.extend(length..length + k);
+        Self::from_parts(permutation, self.flip_set.clone(), self.transverse_flip)
+    }
+
+    /// Restricts the signed permutation to an invariant subset of letters, renumbered in
+    /// increasing order, raising `ValueError` if the subset is not invariant
+    fn restrict(&self, subset: Vec<usize>) -> PyResult<Self> {
+        let length = self.permutation.len();
+        let letters: BTreeSet<usize> = subset.into_iter().collect();
+        if let Some(letter) = letters.iter().find(|letter| **letter >= length) {
+            return Err(PyValueError::new_err(format!(
+                "Letter {} is out of range for a permutation of length {}",
+                letter, length
+            )));
+        }
+        if let Some(letter) = letters
+            .iter()
+            .find(|letter| !letters.contains(&self.permutation[**letter]))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Subset is not invariant: {} is sent to {}",
+                letter, self.permutation[*letter]
+            )));
+        }
+        let renumbered: HashMap<usize, usize> = letters
+            .iter()
+            .enumerate()
+            .map(|(new, old)| (*old, new))
+            .collect();
+        Ok(Self::from_parts(
+            letters
+                .iter()
+                .map(|letter| renumbered[&self.permutation[*letter]])
+                .collect(),
+            self.flip_set
+                .iter()
+                .filter_map(|letter| renumbered.get(letter).copied())
+                .collect(),
+            self.transverse_flip,
+        ))
+    }
+
+    /// Order in the signed permutation group: the least common multiple of the cycle lengths,
+    /// doubled for cycles with sign -1
+    fn order(&self) -> PyResult<u128> {
+        self.cycles()
+            .into_iter()
+            .try_fold(1u128, |order, (cycle, sign)| {
+                let period = if sign < 0 {
+                    2 * cycle.len() as u128
+                } else {
+                    cycle.len() as u128
+                };
+                (order / order.gcd_binary(period)).checked_mul(period)
+            })
+            .ok_or_else(|| PyOverflowError::new_err("Order of the signed permutation overflows"))
+    }
+
+    /// Parity of the number of flips plus the parity of the permutation, i.e. 0 if the signed
+    /// permutation matrix has determinant 1 and 1 otherwise
+    fn sign_parity(&self) -> usize {
+        let cycle_count = self.cycles().len();
+        (self.permutation.len() - cycle_count + self.flip_set.len()) % 2
+    }
+
+    fn __len__(&self) -> usize {
+        self.permutation.len()
+    }
+
+    /// Same as calling the permutation, counting negative indices from the end and raising
+    /// `IndexError` when out of range
+    fn __getitem__(&self, index: isize) -> PyResult<(usize, usize)> {
+        let length = self.permutation.len();
+        let letter = if index < 0 {
+            index + length as isize
+        } else {
+            index
+        };
+        if letter < 0 || letter as usize >= length {
+            return Err(PyIndexError::new_err("letter out of range"));
+        }
+        self.__call__(letter as usize)
+    }
+
+    /// Whether a letter is flipped
+    fn __contains__(&self, letter: usize) -> bool {
+        self.flip_set.contains(&letter)
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> SignedPermutationIterator {
+        SignedPermutationIterator {
+            perm: slf.clone(),
+            letter: 0,
+        }
+    }
+
+    /// Signed permutations are equal when they have the same permutation, flips and transverse
+    /// flip
+    fn __richcmp__(
+        &self,
+        py: Python,
+        other: PyRef<SignedPermutation>,
+        op: CompareOp,
+    ) -> PyResult<Py<PyAny>> {
+        match op {
+            CompareOp::Eq => (*self == *other).into_py_any(py),
+            CompareOp::Ne => (*self != *other).into_py_any(py),
+            _ => Ok(py.NotImplemented()),
+        }
+    }
+
+    fn __hash__(&self) -> isize {
+        checkpoint::permutation_hash(self) as isize
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         let mut s = String::new();
         s.push('[');
@@ -105,226 +905,1713 @@ impl PyObjectProtocol for SignedPermutation {
                 s.push_str(", ");
             }
         }
+        if self.transverse_flip {
+            if !self.permutation.is_empty() {
+                s.push_str(", ");
+            }
+            s.push_str("t -> -t");
+        }
         s.push(']');
         Ok(s)
     }
-}
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
-enum Strand {
-    Transverse(usize),
-    PermutationDirection(usize, usize),
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
+    /// Plain dictionary of the arguments of the constructor, with the flips sorted
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let mut inverse = vec![0; self.permutation.len()];
+        for (letter, image) in self.permutation.iter().enumerate() {
+            inverse[*image] = letter;
+        }
+        let mut flips: Vec<usize> = self.flip_set.iter().copied().collect();
+        flips.sort_unstable();
+        let dict = PyDict::new(py);
+        dict.set_item("permutation", inverse)?;
+        dict.set_item("flips", flips)?;
+        dict.set_item("transverse_flip", self.transverse_flip)?;
+        Ok(dict)
+    }
+
+    /// Inverse of `to_dict`, where `flips` and `transverse_flip` may be left out
+    #[staticmethod]
+    fn from_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let permutation = dict
+            .get_item("permutation")?
+            .ok_or_else(|| PyKeyError::new_err("permutation"))?
+            .extract()?;
+        let flips = match dict.get_item("flips")? {
+            Some(flips) => flips.extract()?,
+            None => Vec::new(),
+        };
+        let transverse_flip = match dict.get_item("transverse_flip")? {
+            Some(transverse_flip) => transverse_flip.extract()?,
+            None => false,
+        };
+        Self::new(permutation, flips, transverse_flip)
+    }
 }
 
-/// Python class to represent a strand
+/// Python iterator over the `(letter, image, flipped)` triples of a signed permutation
+#[cfg(feature = "python")]
 #[pyclass]
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
-struct PyStrand {
-    strand: Strand,
+pub struct SignedPermutationIterator {
+    perm: SignedPermutation,
+    letter: usize,
 }
 
+#[cfg(feature = "python")]
+#[pymethods]
+impl SignedPermutationIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> Option<(usize, usize, usize)> {
+        let letter = slf.letter;
+        let (image, flipped) = slf.perm.__call__(letter).ok()?;
+        slf.letter += 1;
+        Some((letter, image, flipped))
+    }
+}
+
+#[cfg(feature = "python")]
 #[pymethods]
 impl PyStrand {
     #[new]
-    #[args(n = "0")]
-    fn new(type_of_strand: char, m: usize, n: usize) -> PyResult<Self> {
+    #[pyo3(signature = (type_of_strand, m, n = 0))]
+    fn new(type_of_strand: char, m: u64, n: u64) -> PyResult<Self> {
         match type_of_strand {
             't' => Ok(Self {
                 strand: Strand::Transverse(m),
             }),
             'p' => Ok(Self {
-                strand: Strand::PermutationDirection(m, n),
+                strand: Strand::PermutationDirection(narrow(m)?, n),
             }),
-            _ => Err(PermutationError::InvalidStrandType.into()),
+            _ => Err(PermutationError::InvalidStrandType(type_of_strand).into()),
         }
     }
-}
 
-#[pyproto]
-impl PyObjectProtocol for PyStrand {
+    /// Transverse strand of the given index
+    #[staticmethod]
+    fn transverse(index: u64) -> Self {
+        Self {
+            strand: Strand::Transverse(index),
+        }
+    }
+
+    /// Permutation direction strand of the given permutation and copy indices
+    #[staticmethod]
+    fn permutation_direction(perm_index: usize, copy_index: u64) -> Self {
+        Self {
+            strand: Strand::PermutationDirection(perm_index, copy_index),
+        }
+    }
+
+    /// Attributes matched by position in `case PyStrand(kind, index, copy_index)` patterns
+    #[classattr]
+    fn __match_args__() -> (&'static str, &'static str, &'static str) {
+        ("kind", "index", "copy_index")
+    }
+
+    /// `'t'` for a transverse strand and `'p'` for a permutation direction strand
+    #[getter]
+    fn kind(&self) -> char {
+        match self.strand {
+            Strand::Transverse(_) => 't',
+            Strand::PermutationDirection(..) => 'p',
+        }
+    }
+
+    /// Index of a transverse strand, or permutation index of a permutation direction strand
+    #[getter]
+    fn index(&self) -> u64 {
+        match self.strand {
+            Strand::Transverse(index) => index,
+            Strand::PermutationDirection(perm_index, _) => perm_index as u64,
+        }
+    }
+
+    /// Copy index of a permutation direction strand, or `None` for a transverse strand
+    #[getter]
+    fn copy_index(&self) -> Option<u64> {
+        match self.strand {
+            Strand::Transverse(_) => None,
+            Strand::PermutationDirection(_, copy_index) => Some(copy_index),
+        }
+    }
+
+    /// Next strand and whether it flipped, as returned by `get_next_major_strand`
+    #[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+    fn next(
+        &self,
+        perm: &SignedPermutation,
+        m: Multiplicity,
+        n: u64,
+        direction: Direction,
+    ) -> PyResult<(PyStrand, usize)> {
+        get_next_major_strand(perm, m, n, *self, direction)
+    }
+
+    /// Pairs yielded by `trace_orbit` starting from this strand, collected into a list
+    #[pyo3(signature = (perm, m, n, direction = Direction::Left, max_steps = None))]
+    fn orbit(
+        &self,
+        py: Python,
+        perm: &SignedPermutation,
+        m: Multiplicity,
+        n: u64,
+        direction: Direction,
+        max_steps: Option<u64>,
+    ) -> PyResult<Vec<(PyStrand, usize)>> {
+        let mut orbit = trace_orbit(perm, m, n, *self, direction, max_steps)?;
+        py.detach(|| {
+            let mut pairs = Vec::new();
+            while let Some(pair) = orbit.advance()? {
+                pairs.push(pair);
+            }
+            Ok(pairs)
+        })
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("{:?}", self.strand))
     }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        *self
+    }
+
+    /// Plain dictionary of the `kind`, `index` and `copy_index` attributes
+    #[allow(clippy::wrong_self_convention)]
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("kind", self.kind())?;
+        dict.set_item("index", self.index())?;
+        dict.set_item("copy_index", self.copy_index())?;
+        Ok(dict)
+    }
+
+    /// Inverse of `to_dict`, where `copy_index` may be left out for transverse strands
+    #[staticmethod]
+    fn from_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let item = |key: &str| {
+            dict.get_item(key)?
+                .ok_or_else(|| PyKeyError::new_err(key.to_string()))
+        };
+        let kind = item("kind")?.extract()?;
+        let index = item("index")?.extract()?;
+        let copy_index = match dict.get_item("copy_index")? {
+            Some(copy_index) => copy_index.extract::<Option<u64>>()?.unwrap_or(0),
+            None => 0,
+        };
+        Self::new(kind, index, copy_index)
+    }
+
+    /// Strands are ordered with the transverse strands first, each kind ordered by its indices
+    fn __richcmp__(&self, other: PyRef<PyStrand>, op: CompareOp) -> bool {
+        let ordering = self.cmp(&other);
+        match op {
+            CompareOp::Lt => ordering.is_lt(),
+            CompareOp::Le => ordering.is_le(),
+            CompareOp::Eq => ordering.is_eq(),
+            CompareOp::Ne => ordering.is_ne(),
+            CompareOp::Gt => ordering.is_gt(),
+            CompareOp::Ge => ordering.is_ge(),
+        }
+    }
+
+    fn __hash__(&self) -> isize {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish() as isize
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'a, 'py> FromPyObject<'a, 'py> for Direction {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        Ok(obj.extract::<PyBackedStr>()?.parse()?)
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for Direction {
+    type Target = PyString;
+    type Output = Bound<'py, PyString>;
+    type Error = Infallible;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(PyString::new(
+            py,
+            match self {
+                Direction::Left => "left",
+                Direction::Right => "right",
+            },
+        ))
+    }
+}
+
+/// Slope m/n of the resolved multicurve, given as a `fractions.Fraction`, an integer, or a pair
+/// `(m, n)` of nonnegative integers, not both zero, and reduced to lowest terms
+#[cfg(feature = "python")]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Slope {
+    m: u64,
+    n: u64,
+}
+
+#[cfg(feature = "python")]
+impl<'a, 'py> FromPyObject<'a, 'py> for Slope {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        let invalid = || {
+            PyValueError::new_err(format!(
+                "{} is not a nonnegative slope m/n with m and n not both zero",
+                &*obj
+            ))
+        };
+        let (m, n) = match obj.extract::<(Bound<'py, PyAny>, Bound<'py, PyAny>)>() {
+            Ok((m, n)) => (m, n),
+            Err(_) => match (obj.getattr("numerator"), obj.getattr("denominator")) {
+                (Ok(m), Ok(n)) => (m, n),
+                _ => {
+                    return Err(PyTypeError::new_err(
+                        "A slope is a Fraction, an integer or a pair (m, n)",
+                    ))
+                }
+            },
+        };
+        let m: u64 = m.extract().map_err(|_| invalid())?;
+        let n: u64 = n.extract().map_err(|_| invalid())?;
+        let divisor = m.gcd_binary(n);
+        if divisor == 0 {
+            return Err(invalid());
+        }
+        Ok(Slope {
+            m: m / divisor,
+            n: n / divisor,
+        })
+    }
+}
+
+/// Parameters of a counting function taking either `m` and `n`, or `slope`
+#[cfg(feature = "python")]
+fn slope_parameters(
+    m: Option<Multiplicity>,
+    n: Option<u64>,
+    slope: Option<Slope>,
+) -> PyResult<(Multiplicity, u64)> {
+    match (m, n, slope) {
+        (Some(m), Some(n), None) => Ok((m, n)),
+        (None, None, Some(slope)) => Ok((Multiplicity::Uniform(slope.m), slope.n)),
+        _ => Err(PyTypeError::new_err("Pass either both m and n, or slope")),
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'a, 'py> FromPyObject<'a, 'py> for Multiplicity {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(m) = obj.extract::<u64>() {
+            return Ok(Multiplicity::Uniform(m));
+        }
+        Ok(Multiplicity::PerBand(obj.extract()?))
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for Multiplicity {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        match self {
+            Multiplicity::Uniform(m) => m.into_bound_py_any(py),
+            Multiplicity::PerBand(copies) => copies.into_bound_py_any(py),
+        }
+    }
+}
+
+/// Narrows a 64-bit index to a usize, raising `OverflowError` on targets where it does not fit
+#[cfg(feature = "python")]
+fn narrow(value: u64) -> PyResult<usize> {
+    usize::try_from(value).map_err(|_| {
+        PyOverflowError::new_err(format!(
+            "{} does not fit in a usize on this platform",
+            value
+        ))
+    })
+}
+
+/// Raises `ValueError` if `strand` is not one of the strands of the layout
+#[cfg(feature = "python")]
+fn check_strand(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    strand: PyStrand,
+) -> PyResult<()> {
+    if layout.contains(perm, n, strand) {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "{:?} is not a strand of the configuration",
+            strand.strand
+        )))
+    }
 }
 
 /// Returns next major strand and info about whether it flipped
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (perm, m, n, strand, direction = Direction::Left))]
 fn get_next_major_strand(
     perm: &SignedPermutation,
-    m: usize,
-    n: usize,
+    m: Multiplicity,
+    n: u64,
     strand: PyStrand,
-) -> (PyStrand, usize) {
-    let mut flipped = 0;
-    let out_strand: PyStrand = match strand.strand {
-        Strand::PermutationDirection(mut perm_index, mut copy_index) => {
-            if perm.flip_set.contains(&perm_index) {
-                copy_index = m - copy_index - 1;
-                flipped = 1;
-            }
-
-            perm_index = perm.permutation[perm_index];
-            let mut absolute_index = m * perm_index + copy_index;
-
-            if absolute_index + n < perm.permutation.len() * m {
-                absolute_index += n;
-                perm_index = absolute_index / m;
-                copy_index = absolute_index % m;
-                PyStrand {
-                    strand: Strand::PermutationDirection(perm_index, copy_index),
-                }
+    direction: Direction,
+) -> PyResult<(PyStrand, usize)> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    check_strand(perm, &layout, n, strand)?;
+    Ok(next_major_strand(perm, &layout, n, strand, direction))
+}
+
+/// Returns the strand sent to the given strand by `get_next_major_strand`, and whether that step
+/// flipped
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, strand, direction = Direction::Left))]
+fn get_previous_major_strand(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    strand: PyStrand,
+    direction: Direction,
+) -> PyResult<(PyStrand, usize)> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    check_strand(perm, &layout, n, strand)?;
+    Ok(previous_major_strand(perm, &layout, n, strand, direction))
+}
+
+/// Python iterator over the orbit of a strand, see `trace_orbit`
+#[cfg(feature = "python")]
+#[pyclass]
+struct OrbitIterator {
+    perm: SignedPermutation,
+    layout: SlotLayout,
+    n: u64,
+    direction: Direction,
+    start: PyStrand,
+    /// Last strand yielded, or `None` once the orbit has closed
+    current: Option<PyStrand>,
+    /// Number of pairs yielded so far
+    steps: u64,
+    max_steps: Option<u64>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl OrbitIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<(PyStrand, usize)>> {
+        slf.advance()
+    }
+}
+
+#[cfg(feature = "python")]
+impl OrbitIterator {
+    fn advance(&mut self) -> PyResult<Option<(PyStrand, usize)>> {
+        let strand = match self.current {
+            Some(strand) => strand,
+            None => return Ok(None),
+        };
+        if let Some(max_steps) = self.max_steps.filter(|&max_steps| self.steps >= max_steps) {
+            return Err(StepLimitExceeded { max_steps }.into());
+        }
+        self.steps += 1;
+        let (next_strand, flipped) =
+            next_major_strand(&self.perm, &self.layout, self.n, strand, self.direction);
+        self.current = if next_strand == self.start {
+            None
+        } else {
+            Some(next_strand)
+        };
+        Ok(Some((next_strand, flipped)))
+    }
+}
+
+/// Returns an iterator lazily yielding the `(strand, flipped)` pairs of `get_next_major_strand`
+/// along the orbit of `start_strand`, the last pair being the return to `start_strand`. The
+/// iterator raises `StepLimitExceededError` instead of yielding more than `max_steps` pairs.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, start_strand, direction = Direction::Left, max_steps = None))]
+fn trace_orbit(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    start_strand: PyStrand,
+    direction: Direction,
+    max_steps: Option<u64>,
+) -> PyResult<OrbitIterator> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    check_strand(perm, &layout, n, start_strand)?;
+    Ok(OrbitIterator {
+        perm: perm.clone(),
+        layout,
+        n,
+        direction,
+        start: start_strand,
+        current: Some(start_strand),
+        steps: 0,
+        max_steps,
+    })
+}
+
+/// Determines if a given (perm, m, n) triple has only one component and outputs the orientability
+/// The orbit starts from `start` if given, and from the first strand, `Transverse(0)` when n > 0,
+/// otherwise. The empty multicurve for m = n = 0 has no component. Raises
+/// `StepLimitExceededError` if the orbit runs for more than `max_steps` strands.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, m = None, n = None, direction = Direction::Left, start = None, max_steps = None, *,
+    slope = None
+))]
+#[allow(clippy::too_many_arguments)]
+fn has_one_component(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Option<Multiplicity>,
+    n: Option<u64>,
+    direction: Direction,
+    start: Option<PyStrand>,
+    max_steps: Option<u64>,
+    slope: Option<Slope>,
+) -> PyResult<Connectivity> {
+    let (m, n) = slope_parameters(m, n, slope)?;
+    let layout = SlotLayout::new(perm, m, n)?;
+    let starting_strand = match start {
+        Some(strand) => {
+            check_strand(perm, &layout, n, strand)?;
+            strand
+        }
+        None => match layout.strands(perm, n).next() {
+            Some(strand) => strand,
+            None => return Ok((false, 0).into()),
+        },
+    };
+    let connectivity =
+        py.detach(|| orbit_connectivity(perm, &layout, n, starting_strand, direction, max_steps))?;
+    Ok(connectivity.into())
+}
+
+/// Same as `has_one_component`, but traces the orbits of `seeds` strands spread over all strands
+/// in turn, one step at a time, so that a disconnected multicurve is detected as soon as one of
+/// the orbits closes without covering every strand. The orientability returned is that of the
+/// first orbit to close.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, seeds = 8, direction = Direction::Left))]
+fn has_one_component_multi_seed(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    seeds: usize,
+    direction: Direction,
+) -> PyResult<Connectivity> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let total = layout.slots(perm) + n;
+    if total == 0 {
+        return Ok((false, 0).into());
+    }
+    let seeds = (seeds as u64).clamp(1, total) as usize;
+
+    // Each walker follows the orbit of its seed, stopping when it runs into the seed of another
+    // walker, after which it belongs to the group of that walker. Since the orbits are disjoint
+    // cycles, a walker can only run into a strand already visited at the seed of a walker, and
+    // its group's orbit is closed when the active walker of the group runs into its own group.
+    let mut current: Vec<PyStrand> = (0..seeds)
+        .map(|seed| {
+            let index = (seed as u128 * total as u128 / seeds as u128) as u64;
+            let strand = if index < n {
+                Strand::Transverse(index)
             } else {
-                let transverse_index = perm.permutation.len() * m - absolute_index - 1;
-                PyStrand {
-                    strand: Strand::Transverse(transverse_index),
-                }
-            }
+                layout.top_strand(index - n)
+            };
+            PyStrand { strand }
+        })
+        .collect();
+    let mut owner: HashMap<PyStrand, usize> = HashMap::new();
+    let mut group: Vec<usize> = (0..seeds).collect();
+    let mut visited: Vec<u64> = vec![1; seeds];
+    let mut orientability = vec![0; seeds];
+    for (seed, strand) in current.iter().enumerate() {
+        owner.insert(*strand, seed);
+    }
+    let find = |group: &[usize], mut walker: usize| {
+        while group[walker] != walker {
+            walker = group[walker];
         }
-        Strand::Transverse(index) => {
-            if index + perm.permutation.len() * m < n {
-                PyStrand {
-                    strand: Strand::Transverse(index + perm.permutation.len() * m),
+        walker
+    };
+
+    loop {
+        for walker in 0..seeds {
+            if group[walker] != walker {
+                continue;
+            }
+            let (next_strand, flipped) =
+                next_major_strand(perm, &layout, n, current[walker], direction);
+            orientability[walker] ^= flipped;
+            match owner.get(&next_strand) {
+                None => {
+                    owner.insert(next_strand, walker);
+                    visited[walker] += 1;
+                    current[walker] = next_strand;
                 }
-            } else {
-                let absolute_index = n - index - 1;
-                let perm_index = absolute_index / m;
-                let copy_index = absolute_index % m;
-                PyStrand {
-                    strand: Strand::PermutationDirection(perm_index, copy_index),
+                Some(other) if find(&group, *other) == walker => {
+                    return Ok((visited[walker] == total, orientability[walker]).into());
+                }
+                Some(other) => {
+                    let head = find(&group, *other);
+                    group[walker] = head;
+                    visited[head] += visited[walker];
+                    orientability[head] ^= orientability[walker];
                 }
             }
         }
-    };
-    (out_strand, flipped)
+    }
 }
 
-/// Determines if a given (perm, m, n) triple has only one component and outputs the orientability
-/// Can I make this algorithm parallel?
+/// Count components with orientability: ouputs a tuple indicating the number of two-sided and one-sided components
+/// Raises `StepLimitExceededError` if an orbit runs for more than `max_steps` strands, in which
+/// case the cache is bypassed. With `reduce_bigons`, the bigons between the transverse curve and
+/// the curve of the permutation are removed first.
+#[cfg(feature = "python")]
 #[pyfunction]
-fn has_one_component(perm: &SignedPermutation, m: usize, n: usize) -> (bool, usize) {
-    let expected_orbit_length = m * perm.permutation.len() + n;
-    let mut actual_orbit_length = 1;
+#[pyo3(signature = (
+    perm, m = None, n = None, direction = Direction::Left, max_steps = None, *, slope = None,
+    reduce_bigons = false
+))]
+#[allow(clippy::too_many_arguments)]
+fn count_components_with_orientability(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Option<Multiplicity>,
+    n: Option<u64>,
+    direction: Direction,
+    max_steps: Option<u64>,
+    slope: Option<Slope>,
+    reduce_bigons: bool,
+) -> PyResult<ComponentCount> {
+    let (m, n) = slope_parameters(m, n, slope)?;
+    let reduced = if reduce_bigons {
+        Some(bigons::reduce_for_counting(perm, &m)?)
+    } else {
+        None
+    };
+    let (perm, disjoint) = match &reduced {
+        Some((reduced, disjoint)) => (reduced, *disjoint),
+        None => (perm, (0, 0)),
+    };
+    let layout = SlotLayout::new(perm, m, n)?;
+    let (two_sided, one_sided) = py.detach(|| match (layout, max_steps) {
+        (SlotLayout::Uniform(m), None) => Ok(cached_component_counts(perm, m, n, direction)),
+        (layout, max_steps) => component_counts_bounded(perm, &layout, n, direction, max_steps),
+    })?;
+    Ok((two_sided + disjoint.0, one_sided + disjoint.1).into())
+}
 
-    let starting_strand = PyStrand {
-        strand: Strand::Transverse(0),
+/// Counts the components like `count_components_with_orientability`, without telling the
+/// two-sided ones from the one-sided ones
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, m = None, n = None, direction = Direction::Left, *, slope = None, reduce_bigons = false
+))]
+fn count_components(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Option<Multiplicity>,
+    n: Option<u64>,
+    direction: Direction,
+    slope: Option<Slope>,
+    reduce_bigons: bool,
+) -> PyResult<u64> {
+    let (m, n) = slope_parameters(m, n, slope)?;
+    let reduced = if reduce_bigons {
+        Some(bigons::reduce_for_counting(perm, &m)?)
+    } else {
+        None
     };
-    let mut next_strand_with_orientability = get_next_major_strand(perm, m, n, starting_strand);
-    let mut next_strand = next_strand_with_orientability.0;
-    let mut orientability = next_strand_with_orientability.1;
-    while next_strand != starting_strand {
-        next_strand_with_orientability = get_next_major_strand(perm, m, n, next_strand);
-        next_strand = next_strand_with_orientability.0;
-        orientability = (orientability + next_strand_with_orientability.1) % 2;
-        actual_orbit_length += 1;
-    }
+    let (perm, disjoint) = match &reduced {
+        Some((reduced, disjoint)) => (reduced, *disjoint),
+        None => (perm, (0, 0)),
+    };
+    let layout = SlotLayout::new(perm, m, n)?;
+    Ok(py.detach(|| component_total(perm, &layout, n, direction)) + disjoint.0 + disjoint.1)
+}
 
-    (expected_orbit_length == actual_orbit_length, orientability)
+/// Counts the boundary circles of a regular neighborhood of the resolved multicurve: two for each
+/// two-sided component, and one for each one-sided component
+#[cfg(feature = "python")]
+#[pyfunction]
+fn neighborhood_boundary_count(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+) -> PyResult<u64> {
+    let counts = count_components_with_orientability(
+        py,
+        perm,
+        Some(m),
+        Some(n),
+        Direction::Left,
+        None,
+        None,
+        false,
+    )?;
+    Ok(2 * counts.two_sided + counts.one_sided)
 }
 
-/// Count components with orientability: ouputs a tuple indicating the number of two-sided and one-sided components
-/// Can I make this parallel?
+/// Outputs, for each component, its orbit length (number of strands) and orientability
+/// (0 for two-sided, 1 for one-sided), raising `StepLimitExceededError` if an orbit runs for
+/// more than `max_steps` strands
+#[cfg(feature = "python")]
 #[pyfunction]
-fn count_components_with_orientability(
+#[pyo3(signature = (perm, m, n, max_steps = None))]
+fn component_profile(
+    py: Python,
     perm: &SignedPermutation,
-    m: usize,
-    n: usize,
-) -> (usize, usize) {
-    let mut two_sided_components = 0;
-    let mut one_sided_components = 0;
-
-    let mut strands = BTreeSet::new();
-    for i in 0..n {
-        strands.insert(PyStrand {
-            strand: Strand::Transverse(i),
-        });
+    m: Multiplicity,
+    n: u64,
+    max_steps: Option<u64>,
+) -> PyResult<Vec<(u64, usize)>> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let mut profile = Vec::new();
+    py.detach(|| {
+        trace_components_bounded(
+            perm,
+            &layout,
+            n,
+            Direction::Left,
+            max_steps,
+            &mut profile,
+            |_, _| {},
+            |profile, length, orientability| profile.push((length, orientability)),
+        )
+    })?;
+    Ok(profile)
+}
+
+/// Component of each strand, and the length, orientability and first strand of each component
+#[cfg(feature = "python")]
+type ComponentLabels = (Vec<u64>, Vec<(u64, usize, PyStrand)>);
+
+/// Outputs the component of every strand, indexed by its position among the transverse strands
+/// followed by the copies of each band in turn, together with one tuple `(length, orientability,
+/// first_strand)` per component, listed in the same order as `component_profile`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn component_labels(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<ComponentLabels> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let mut labels = vec![0; narrow(layout.slots(perm) + n)?];
+    let mut components = Vec::new();
+    py.detach(|| {
+        // Label of the component being traced and its first strand
+        let mut state = (0, None);
+        trace_components(
+            perm,
+            &layout,
+            n,
+            direction,
+            &mut state,
+            |(label, first), strand| {
+                labels[layout.strand_index(n, strand) as usize] = *label;
+                first.get_or_insert(strand);
+            },
+            |(label, first), length, orientability| {
+                components.push((length, orientability, first.take().unwrap()));
+                *label += 1;
+            },
+        )
+    });
+    Ok((labels, components))
+}
+
+/// Outputs the resolved multicurve as a measured multicurve: scaling the multiplicities and n by
+/// their greatest common divisor t, the resolution is t parallel copies of the resolution of the
+/// primitive parameters, so each component of the latter is listed as a tuple `(length,
+/// orientability, weight)` with weight t
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn measured_multicurve(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<Vec<(u64, usize, u64)>> {
+    let weight = match &m {
+        Multiplicity::Uniform(m) => m.gcd_binary(n),
+        Multiplicity::PerBand(copies) => copies.iter().fold(n, |weight, m| weight.gcd_binary(*m)),
+    };
+    if weight == 0 {
+        return Ok(Vec::new());
     }
-    for j in 0..perm.permutation.len() {
-        for k in 0..m {
-            strands.insert(PyStrand {
-                strand: Strand::PermutationDirection(j, k),
-            });
+    let primitive = match m {
+        Multiplicity::Uniform(m) => Multiplicity::Uniform(m / weight),
+        Multiplicity::PerBand(copies) => {
+            Multiplicity::PerBand(copies.into_iter().map(|m| m / weight).collect())
         }
+    };
+    let n = n / weight;
+    let layout = SlotLayout::new(perm, primitive, n)?;
+    let mut components = Vec::new();
+    py.detach(|| {
+        trace_components(
+            perm,
+            &layout,
+            n,
+            direction,
+            &mut components,
+            |_, _| {},
+            |components, length, orientability| components.push((length, orientability, weight)),
+        )
+    });
+    Ok(components)
+}
+
+/// An (m,n) pair together with its (two-sided, one-sided) component counts
+#[cfg(any(feature = "python", feature = "ffi"))]
+type SweepResult = ((u64, u64), (u64, u64));
+
+/// How often a long-running parallel computation polls the interpreter for signals
+#[cfg(feature = "python")]
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Turns logging of every strand transition with its index arithmetic to stderr on or off
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (enabled = true))]
+fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+/// Dedicated rayon pool used by the sweeps, if one has been configured with `set_num_threads`
+#[cfg(feature = "python")]
+static THREAD_POOL: Mutex<Option<Arc<rayon::ThreadPool>>> = Mutex::new(None);
+
+/// Restricts all subsequent parallel computations to `num_threads` threads.
+/// Passing 0 goes back to rayon's global pool, which uses all available cores.
+#[cfg(feature = "python")]
+#[pyfunction]
+fn set_num_threads(num_threads: usize) -> PyResult<()> {
+    let pool = if num_threads == 0 {
+        None
+    } else {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+        Some(Arc::new(pool))
+    };
+    *THREAD_POOL.lock().unwrap() = pool;
+    Ok(())
+}
+
+/// Returns the number of threads parallel computations currently run on
+#[cfg(feature = "python")]
+#[pyfunction]
+fn get_num_threads() -> usize {
+    match THREAD_POOL.lock().unwrap().as_ref() {
+        Some(pool) => pool.current_num_threads(),
+        None => rayon::current_num_threads(),
     }
+}
 
-    while !strands.is_empty() {
-        let first_strand = strands.pop_first().unwrap();
-        let next_strand_with_orientability = get_next_major_strand(perm, m, n, first_strand);
-        let mut orientability = next_strand_with_orientability.1;
-        let mut next_strand = next_strand_with_orientability.0;
-        while next_strand != first_strand {
-            let next_strand_with_orientability = get_next_major_strand(perm, m, n, next_strand);
-            strands.remove(&next_strand);
-            orientability += next_strand_with_orientability.1;
-            next_strand = next_strand_with_orientability.0;
+/// Runs `job` on a separate thread while the calling thread periodically checks for pending
+/// Python signals. On Ctrl-C the cancellation flag handed to `job` is raised, the job is waited
+/// on, and the resulting `KeyboardInterrupt` is returned. Jobs should poll the flag and return
+/// `None` once it is set. The job runs inside the pool configured by `set_num_threads`, if any.
+#[cfg(feature = "python")]
+fn run_interruptible<T, F>(py: Python, job: F) -> PyResult<T>
+where
+    T: Send,
+    F: FnOnce(&AtomicBool) -> Option<T> + Send,
+{
+    let cancelled = AtomicBool::new(false);
+    let finished = AtomicBool::new(false);
+    let caller = thread::current();
+    let pool = THREAD_POOL.lock().unwrap().clone();
+
+    thread::scope(|scope| {
+        let handle = scope.spawn(|| {
+            let output = match pool {
+                Some(pool) => pool.install(|| job(&cancelled)),
+                None => job(&cancelled),
+            };
+            finished.store(true, Ordering::Release);
+            caller.unpark();
+            output
+        });
+
+        while !finished.load(Ordering::Acquire) {
+            py.detach(|| thread::park_timeout(SIGNAL_POLL_INTERVAL));
+            if let Err(err) = py.check_signals() {
+                cancelled.store(true, Ordering::Relaxed);
+                py.detach(|| handle.join())
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                return Err(err);
+            }
         }
-        if orientability % 2 == 0 {
-            two_sided_components += 1;
-        } else {
-            one_sided_components += 1;
+
+        handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            .ok_or_else(|| PyKeyboardInterrupt::new_err("Computation cancelled"))
+    })
+}
+
+/// Checks that every (m,n) pair of a sweep up to `complexity` has a representable strand count.
+/// Since m,n < complexity, no pair has more strands than (complexity, 0) when len > 0.
+#[cfg(feature = "python")]
+fn check_sweep_bounds(perm: &SignedPermutation, complexity: u64) -> PyResult<()> {
+    strand_count(perm, complexity, 0)?;
+    Ok(())
+}
+
+/// Parallel iterator over all (m,n) pairs with gcd(m,n) = 1 and m+n < complexity
+#[cfg(feature = "python")]
+fn coprime_pairs_upto(complexity: u64) -> impl ParallelIterator<Item = (u64, u64)> {
+    sweep_pairs_upto(complexity, false)
+}
+
+/// Parallel iterator over all (m,n) pairs with m,n > 0 and m+n < complexity, skipping those with
+/// gcd(m,n) > 1 unless `include_non_coprime` is set
+#[cfg(any(feature = "python", feature = "ffi"))]
+fn sweep_pairs_upto(
+    complexity: u64,
+    include_non_coprime: bool,
+) -> impl ParallelIterator<Item = (u64, u64)> {
+    sweep_pairs_between(2, complexity, include_non_coprime)
+}
+
+/// Same as `sweep_pairs_upto`, restricted to the pairs with start <= m+n
+#[cfg(any(feature = "python", feature = "ffi"))]
+fn sweep_pairs_between(
+    start: u64,
+    complexity: u64,
+    include_non_coprime: bool,
+) -> impl ParallelIterator<Item = (u64, u64)> {
+    (start.max(2)..complexity)
+        .into_par_iter()
+        .flat_map(move |k| {
+            (1..k)
+                .into_par_iter()
+                .filter(move |n| include_non_coprime || k.gcd_binary(*n) == 1)
+                .map(move |n| (k - n, n))
+        })
+}
+
+/// How the pairs of a sweep are handed out to the threads
+#[cfg(feature = "python")]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum ChunkStrategy {
+    /// Rayon's adaptive splitting, treating every pair as equally expensive
+    Adaptive,
+    /// Chunks of roughly equal total cost m * len + n, most expensive first
+    Cost,
+}
+
+#[cfg(feature = "python")]
+impl<'a, 'py> FromPyObject<'a, 'py> for ChunkStrategy {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        match &*obj.extract::<PyBackedStr>()? {
+            "adaptive" => Ok(ChunkStrategy::Adaptive),
+            "cost" => Ok(ChunkStrategy::Cost),
+            _ => Err(PermutationError::InvalidChunkStrategy.into()),
+        }
+    }
+}
+
+/// Number of chunks per thread with `ChunkStrategy::Cost`, leaving room for work stealing
+#[cfg(feature = "python")]
+const CHUNKS_PER_THREAD: u64 = 4;
+
+/// Hands out `pairs` according to `strategy`. With `ChunkStrategy::Cost` the pairs are sorted by
+/// decreasing number of strands and cut into consecutive chunks of about equal total cost, each
+/// chunk being processed by a single thread.
+#[cfg(feature = "python")]
+fn schedule_pairs(
+    perm: &SignedPermutation,
+    pairs: impl ParallelIterator<Item = (u64, u64)>,
+    strategy: ChunkStrategy,
+) -> impl ParallelIterator<Item = (u64, u64)> {
+    match strategy {
+        ChunkStrategy::Adaptive => Either::Left(pairs),
+        ChunkStrategy::Cost => {
+            let length = perm.permutation.len() as u64;
+            let cost = |&(m, n): &(u64, u64)| m * length + n;
+            let mut pairs: Vec<(u64, u64)> = pairs.collect();
+            pairs.sort_unstable_by_key(|pair| std::cmp::Reverse(cost(pair)));
+            let total: u64 = pairs.iter().map(cost).sum();
+            let chunks = CHUNKS_PER_THREAD * rayon::current_num_threads() as u64;
+            let target = (total / chunks).max(1);
+            let mut chunks = vec![Vec::new()];
+            let mut chunk_cost = 0;
+            for pair in pairs {
+                if chunk_cost >= target {
+                    chunks.push(Vec::new());
+                    chunk_cost = 0;
+                }
+                chunk_cost += cost(&pair);
+                chunks.last_mut().unwrap().push(pair);
+            }
+            Either::Right(chunks.into_par_iter().flat_map_iter(Vec::into_iter))
         }
     }
+}
 
-    (two_sided_components, one_sided_components)
+/// Sort key putting (m,n) pairs in increasing order of complexity m+n, then of n. Sweep results
+/// are returned in this order so that identical sweeps give identical lists.
+#[cfg(any(feature = "python", feature = "ffi"))]
+pub(crate) fn complexity_order(&(m, n): &(u64, u64)) -> (u64, u64) {
+    (m + n, n)
+}
+
+/// Wall-clock time in seconds and number of orbit steps spent on an (m,n) pair
+#[cfg(feature = "python")]
+type PairProfile = ((u64, u64), f64, u64);
+
+/// Counts the components of every pair in parallel. When profiling, the cache is bypassed and
+/// each pair is timed, every strand being one orbit step.
+#[cfg(feature = "python")]
+fn count_pairs(
+    py: Python,
+    perm: &SignedPermutation,
+    pairs: impl ParallelIterator<Item = (u64, u64)>,
+    direction: Direction,
+    profile: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<(Vec<SweepResult>, Vec<PairProfile>)> {
+    let length = perm.permutation.len() as u64;
+    let timed: Vec<(SweepResult, Option<PairProfile>)> = run_interruptible(py, |cancelled| {
+        schedule_pairs(perm, pairs, chunk_strategy)
+            .map(|(m, n)| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                if !profile {
+                    return Some((
+                        ((m, n), cached_component_counts(perm, m, n, direction)),
+                        None,
+                    ));
+                }
+                let start = Instant::now();
+                let counts = component_counts(perm, &SlotLayout::Uniform(m), n, direction);
+                let seconds = start.elapsed().as_secs_f64();
+                Some((((m, n), counts), Some(((m, n), seconds, length * m + n))))
+            })
+            .collect()
+    })?;
+    let (results, profiles): (Vec<SweepResult>, Vec<Option<PairProfile>>) =
+        timed.into_iter().unzip();
+    Ok((results, profiles.into_iter().flatten().collect()))
+}
+
+/// The results alone, or together with their profiles if `profile` is set
+#[cfg(feature = "python")]
+fn with_profile(
+    py: Python,
+    (results, profiles): (Vec<SweepResult>, Vec<PairProfile>),
+    profile: bool,
+) -> PyResult<Py<PyAny>> {
+    if profile {
+        (entries(results), profiles).into_py_any(py)
+    } else {
+        entries(results).into_py_any(py)
+    }
 }
 
 /// Function to count components of all (m,n) pairs up to a complexity in parallel
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, direction = Direction::Left, include_non_coprime = false, profile = false,
+    chunk_strategy = ChunkStrategy::Adaptive
+))]
 fn count_components_upto_complexity(
+    py: Python,
     perm: &SignedPermutation,
-    complexity: usize,
-) -> Vec<((usize, usize), (usize, usize))> {
-    (2..complexity)
-        .into_par_iter()
-        .flat_map(|k| {
-            (1..k)
+    complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+    profile: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Py<PyAny>> {
+    check_sweep_bounds(perm, complexity)?;
+    let (mut results, mut profiles) = count_pairs(
+        py,
+        perm,
+        sweep_pairs_upto(complexity, include_non_coprime),
+        direction,
+        profile,
+        chunk_strategy,
+    )?;
+    results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    profiles.sort_unstable_by_key(|(pair, _, _)| complexity_order(pair));
+    with_profile(py, (results, profiles), profile)
+}
+
+/// Packs integers into an `array.array` of unsigned 64-bit integers, which exposes them through
+/// the buffer protocol
+#[cfg(feature = "python")]
+fn u64_array<'py>(py: Python<'py>, values: &[u64]) -> PyResult<Bound<'py, PyAny>> {
+    let bytes: Vec<u8> = values
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect();
+    let array = py.import("array")?.getattr("array")?.call1(("Q",))?;
+    array.call_method1("frombytes", (PyBytes::new(py, &bytes),))?;
+    Ok(array)
+}
+
+/// Same as `count_components_upto_complexity`, returning the results as columns: a dictionary
+/// of arrays of unsigned 64-bit integers `m`, `n`, `two_sided` and `one_sided`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, complexity, direction = Direction::Left, include_non_coprime = false))]
+fn sweep_to_columns<'py>(
+    py: Python<'py>,
+    perm: &SignedPermutation,
+    complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    check_sweep_bounds(perm, complexity)?;
+    let (mut results, _) = count_pairs(
+        py,
+        perm,
+        sweep_pairs_upto(complexity, include_non_coprime),
+        direction,
+        false,
+        ChunkStrategy::Adaptive,
+    )?;
+    results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    let columns = PyDict::new(py);
+    let column =
+        |select: fn(&SweepResult) -> u64| -> Vec<u64> { results.iter().map(select).collect() };
+    columns.set_item("m", u64_array(py, &column(|((m, _), _)| *m))?)?;
+    columns.set_item("n", u64_array(py, &column(|((_, n), _)| *n))?)?;
+    columns.set_item("two_sided", u64_array(py, &column(|(_, (two, _))| *two))?)?;
+    columns.set_item("one_sided", u64_array(py, &column(|(_, (_, one))| *one))?)?;
+    Ok(columns)
+}
+
+/// Same as `count_components_upto_complexity`, restricted to the pairs with k_min <= m+n < k_max,
+/// so that consecutive ranges partition a sweep
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, k_min, k_max, direction = Direction::Left, include_non_coprime = false, profile = false,
+    chunk_strategy = ChunkStrategy::Adaptive
+))]
+#[allow(clippy::too_many_arguments)]
+fn count_components_in_range(
+    py: Python,
+    perm: &SignedPermutation,
+    k_min: u64,
+    k_max: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+    profile: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Py<PyAny>> {
+    check_sweep_bounds(perm, k_max)?;
+    let (mut results, mut profiles) = count_pairs(
+        py,
+        perm,
+        sweep_pairs_between(k_min, k_max, include_non_coprime),
+        direction,
+        profile,
+        chunk_strategy,
+    )?;
+    results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    profiles.sort_unstable_by_key(|(pair, _, _)| complexity_order(pair));
+    with_profile(py, (results, profiles), profile)
+}
+
+/// Shard among `num_shards` that the pair (m,n) belongs to. Hashing the pair spreads every range
+/// of complexities evenly over the shards, and does not depend on the platform or the build.
+#[cfg(feature = "python")]
+fn shard_of((m, n): (u64, u64), num_shards: u64) -> u64 {
+    SplitMix64(SplitMix64(m).next() ^ n).next() % num_shards
+}
+
+/// Same as `count_components_upto_complexity`, restricted to the pairs of shard `shard_index`
+/// among `num_shards`, so that the shards partition a sweep
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, shard_index, num_shards, direction = Direction::Left,
+    include_non_coprime = false, profile = false, chunk_strategy = ChunkStrategy::Adaptive
+))]
+#[allow(clippy::too_many_arguments)]
+fn count_components_shard(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    shard_index: u64,
+    num_shards: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+    profile: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Py<PyAny>> {
+    if shard_index >= num_shards {
+        return Err(PyValueError::new_err(format!(
+            "Shard index {} is not less than the number of shards {}",
+            shard_index, num_shards
+        )));
+    }
+    check_sweep_bounds(perm, complexity)?;
+    let pairs = sweep_pairs_upto(complexity, include_non_coprime)
+        .filter(move |&pair| shard_of(pair, num_shards) == shard_index);
+    let (mut results, mut profiles) =
+        count_pairs(py, perm, pairs, direction, profile, chunk_strategy)?;
+    results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    profiles.sort_unstable_by_key(|(pair, _, _)| complexity_order(pair));
+    with_profile(py, (results, profiles), profile)
+}
+
+/// Counts the components of every (m,n) pair in `pairs` in parallel, returning the results in
+/// the order of the pairs
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, pairs, direction = Direction::Left, profile = false))]
+fn count_components_batch(
+    py: Python,
+    perm: &SignedPermutation,
+    pairs: Vec<Vec<u64>>,
+    direction: Direction,
+    profile: bool,
+) -> PyResult<Py<PyAny>> {
+    let pairs = pairs
+        .into_iter()
+        .map(|pair| match pair[..] {
+            [m, n] => {
+                strand_count(perm, m, n)?;
+                Ok((m, n))
+            }
+            _ => Err(PyValueError::new_err(format!(
+                "Expected a pair (m, n), got {} values",
+                pair.len()
+            ))),
+        })
+        .collect::<PyResult<Vec<(u64, u64)>>>()?;
+    let counted = count_pairs(
+        py,
+        perm,
+        pairs.into_par_iter(),
+        direction,
+        profile,
+        ChunkStrategy::Adaptive,
+    )?;
+    with_profile(py, counted, profile)
+}
+
+/// Extends a sweep to all (m,n) pairs with m+n < new_complexity, computing only the complexity
+/// levels m+n that do not appear in `previous`, whose levels are assumed complete. `previous` is
+/// either a list of sweep results or the path of a results file for `perm`, which is then
+/// rewritten with the merged results.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    previous, perm, new_complexity, direction = Direction::Left, include_non_coprime = false
+))]
+fn extend_sweep(
+    py: Python,
+    previous: &Bound<'_, PyAny>,
+    perm: &SignedPermutation,
+    new_complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+) -> PyResult<Vec<SweepEntry>> {
+    check_sweep_bounds(perm, new_complexity)?;
+    let path = previous.extract::<PyBackedStr>().ok();
+    let mut results = match &path {
+        Some(path) => read_results_file(path, perm)?,
+        None => extract_results(previous.extract()?)?,
+    };
+    let covered: HashSet<u64> = results.iter().map(|((m, n), _)| m + n).collect();
+    let pairs = sweep_pairs_upto(new_complexity, include_non_coprime)
+        .filter(|(m, n)| !covered.contains(&(m + n)));
+    let (computed, _) = count_pairs(py, perm, pairs, direction, false, ChunkStrategy::Adaptive)?;
+
+    results.extend(computed);
+    results.sort_unstable_by_key(|(pair, _)| complexity_order(pair));
+    if let Some(path) = &path {
+        write_results_file(path, perm, &results)?;
+    }
+    Ok(entries(results))
+}
+
+/// Which multicurves a filtered sweep keeps, by the orientability of their components
+#[cfg(feature = "python")]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum OrientabilityFilter {
+    AllTwoSided,
+    AllOneSided,
+    Mixed,
+}
+
+#[cfg(feature = "python")]
+impl OrientabilityFilter {
+    /// Whether a multicurve with the given numbers of two-sided and one-sided components is kept
+    fn keeps(self, (two_sided, one_sided): (u64, u64)) -> bool {
+        match self {
+            OrientabilityFilter::AllTwoSided => one_sided == 0,
+            OrientabilityFilter::AllOneSided => two_sided == 0,
+            OrientabilityFilter::Mixed => two_sided != 0 && one_sided != 0,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'a, 'py> FromPyObject<'a, 'py> for OrientabilityFilter {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        match &*obj.extract::<PyBackedStr>()? {
+            "all_two_sided" => Ok(OrientabilityFilter::AllTwoSided),
+            "all_one_sided" => Ok(OrientabilityFilter::AllOneSided),
+            "mixed" => Ok(OrientabilityFilter::Mixed),
+            _ => Err(PermutationError::InvalidOrientabilityFilter.into()),
+        }
+    }
+}
+
+/// Lists the `(m,n)` up to a given complexity whose numbers of two-sided and one-sided components
+/// are kept by `keep`
+#[cfg(feature = "python")]
+fn filtered_sweep(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    keep: impl Fn((u64, u64)) -> bool + Sync,
+    direction: Direction,
+    include_non_coprime: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Vec<(u64, u64)>> {
+    check_sweep_bounds(perm, complexity)?;
+    let pairs = sweep_pairs_upto(complexity, include_non_coprime);
+    let mut pairs: Vec<(u64, u64)> = run_interruptible(py, |cancelled| {
+        schedule_pairs(perm, pairs, chunk_strategy)
+            .map(|(m, n)| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                Some(((m, n), cached_component_counts(perm, m, n, direction)))
+            })
+            .filter(|result| !matches!(result, Some((_, counts)) if !keep(*counts)))
+            .map(|result| result.map(|(a, _)| a))
+            .collect()
+    })?;
+    pairs.sort_unstable_by_key(complexity_order);
+    Ok(pairs)
+}
+
+/// Number of (m,n) pairs of each complexity m+n giving each (two-sided, one-sided) outcome
+#[cfg(feature = "python")]
+type Histogram = BTreeMap<u64, BTreeMap<(u64, u64), u64>>;
+
+/// Function to count, for each complexity m+n up to a given complexity, how many (m,n) pairs
+/// give each number of two-sided and one-sided components, aggregating in parallel
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, direction = Direction::Left, include_non_coprime = false,
+    chunk_strategy = ChunkStrategy::Adaptive
+))]
+fn component_count_histogram(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Histogram> {
+    check_sweep_bounds(perm, complexity)?;
+    let pairs = sweep_pairs_upto(complexity, include_non_coprime);
+    run_interruptible(py, |cancelled| {
+        schedule_pairs(perm, pairs, chunk_strategy)
+            .map(|(m, n)| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                Some((m + n, cached_component_counts(perm, m, n, direction)))
+            })
+            .try_fold(Histogram::new, |mut histogram, outcome| {
+                let (k, counts) = outcome?;
+                *histogram.entry(k).or_default().entry(counts).or_default() += 1;
+                Some(histogram)
+            })
+            .try_reduce(Histogram::new, |mut histogram, other| {
+                for (k, outcomes) in other {
+                    let level = histogram.entry(k).or_default();
+                    for (counts, pairs) in outcomes {
+                        *level.entry(counts).or_default() += pairs;
+                    }
+                }
+                Some(histogram)
+            })
+    })
+}
+
+/// Function to find the coprime (m,n) of smallest complexity m+n, then smallest n, whose resolved
+/// multicurve is a single two-sided curve. Each complexity is searched in parallel, and the search
+/// stops at the first one with a solution, or before `max_complexity` if given.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, max_complexity = None, direction = Direction::Left))]
+fn find_smallest_connected(
+    py: Python,
+    perm: &SignedPermutation,
+    max_complexity: Option<u64>,
+    direction: Direction,
+) -> PyResult<Option<(u64, u64)>> {
+    run_interruptible(py, |cancelled| {
+        for k in 2u64.. {
+            if max_complexity.is_some_and(|complexity| k >= complexity) {
+                break;
+            }
+            if let Err(err) = check_sweep_bounds(perm, k) {
+                return Some(Err(err));
+            }
+            let found = (1..k)
                 .into_par_iter()
-                .filter(move |n| k.gcd_binary(*n) == 1)
-                .map(move |n| {
-                    let m = k - n;
-                    ((m, n), count_components_with_orientability(perm, m, n))
+                .filter(|n| k.gcd_binary(*n) == 1)
+                .map(|n| (k - n, n))
+                .find_first(|&(m, n)| {
+                    !cancelled.load(Ordering::Relaxed)
+                        && connected_orientability(perm, &SlotLayout::Uniform(m), n, direction)
+                            == Some(0)
+                });
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            if found.is_some() {
+                return Some(Ok(found));
+            }
+        }
+        Some(Ok(None))
+    })?
+}
+
+/// Number of pairs, total number of components and number of one-sided components at each
+/// complexity level m+n
+#[cfg(feature = "python")]
+type LevelTotals = BTreeMap<u64, (u64, u64, u64)>;
+
+/// Averages the component counts of the sweep up to `complexity` over each complexity level m+n,
+/// returning the levels, the mean number of components and the mean number of one-sided
+/// components as three lists
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (perm, complexity, direction = Direction::Left, include_non_coprime = false))]
+fn mean_components_by_complexity(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+) -> PyResult<(Vec<u64>, Vec<f64>, Vec<f64>)> {
+    check_sweep_bounds(perm, complexity)?;
+    let add = |mut totals: LevelTotals, (level, (pairs, components, one_sided))| {
+        let entry = totals.entry(level).or_insert((0, 0, 0));
+        *entry = (entry.0 + pairs, entry.1 + components, entry.2 + one_sided);
+        totals
+    };
+    let totals = run_interruptible(py, |cancelled| {
+        sweep_pairs_upto(complexity, include_non_coprime)
+            .map(|(m, n)| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let (two_sided, one_sided) = cached_component_counts(perm, m, n, direction);
+                Some((m + n, (1, two_sided + one_sided, one_sided)))
+            })
+            .try_fold(LevelTotals::new, |totals, level| Some(add(totals, level?)))
+            .try_reduce(LevelTotals::new, |a, b| Some(b.into_iter().fold(a, add)))
+    })?;
+
+    let mut curves = (Vec::new(), Vec::new(), Vec::new());
+    for (level, (pairs, components, one_sided)) in totals {
+        curves.0.push(level);
+        curves.1.push(components as f64 / pairs as f64);
+        curves.2.push(one_sided as f64 / pairs as f64);
+    }
+    Ok(curves)
+}
+
+/// Function to compute the fraction of coprime (m,n) with m+n < complexity whose resolved
+/// multicurve is connected. The complexities are processed in increasing order, and after every
+/// `report_every` of them, as well as after the last one, the partial result
+/// `(k, connected, total, density)` over all m+n <= k is recorded and passed to `callback`.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, report_every = 1000, callback = None, direction = Direction::Left
+))]
+fn connected_density(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    report_every: u64,
+    callback: Option<Py<PyAny>>,
+    direction: Direction,
+) -> PyResult<Vec<(u64, u64, u64, f64)>> {
+    check_sweep_bounds(perm, complexity)?;
+    let report_every = report_every.max(1);
+    let mut reports = Vec::new();
+    let (mut connected, mut total) = (0, 0);
+    let mut start = 2;
+    while start < complexity {
+        // Levels up to the next multiple of `report_every`, or the last level
+        let end = ((start / report_every + 1) * report_every).min(complexity - 1);
+        let (chunk_connected, chunk_total) = run_interruptible(py, |cancelled| {
+            sweep_pairs_between(start, end + 1, false)
+                .map(|(m, n)| {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    let layout = SlotLayout::Uniform(m);
+                    let connected = connected_orientability(perm, &layout, n, direction).is_some();
+                    Some((connected as u64, 1))
                 })
-        })
-        .collect()
+                .try_reduce(|| (0, 0), |a, b| Some((a.0 + b.0, a.1 + b.1)))
+        })?;
+        connected += chunk_connected;
+        total += chunk_total;
+        let report = (end, connected, total, connected as f64 / total as f64);
+        if let Some(callback) = &callback {
+            callback.call1(py, report)?;
+        }
+        reports.push(report);
+        start = end + 1;
+    }
+    Ok(reports)
 }
 
 /// Function to list only two-sided multicurves up to a given complexity
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, direction = Direction::Left, include_non_coprime = false,
+    chunk_strategy = ChunkStrategy::Adaptive
+))]
 fn two_sided_multicurves_upto_complexity(
+    py: Python,
     perm: &SignedPermutation,
-    complexity: usize,
-) -> Vec<(usize, usize)> {
-    (2..complexity)
-        .into_par_iter()
-        .flat_map(|k| {
-            (1..k)
-                .into_par_iter()
-                .filter(move |n| k.gcd_binary(*n) == 1)
-                .map(move |n| {
-                    let m = k - n;
-                    ((m, n), count_components_with_orientability(perm, m, n))
-                })
-                .filter(|(_, (_, o))| *o == 0)
-                .map(|(a, _)| a)
-        })
-        .collect()
+    complexity: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Vec<(u64, u64)>> {
+    filtered_sweep(
+        py,
+        perm,
+        complexity,
+        |counts| OrientabilityFilter::AllTwoSided.keeps(counts),
+        direction,
+        include_non_coprime,
+        chunk_strategy,
+    )
+}
+
+/// Function to list the multicurves up to a given complexity whose components are all two-sided,
+/// all one-sided, or of both kinds
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, orientability_filter, direction = Direction::Left,
+    include_non_coprime = false, chunk_strategy = ChunkStrategy::Adaptive
+))]
+fn multicurves_upto_complexity(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    orientability_filter: OrientabilityFilter,
+    direction: Direction,
+    include_non_coprime: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Vec<(u64, u64)>> {
+    filtered_sweep(
+        py,
+        perm,
+        complexity,
+        |counts| orientability_filter.keeps(counts),
+        direction,
+        include_non_coprime,
+        chunk_strategy,
+    )
+}
+
+/// Function to list the multicurves up to a given complexity with exactly `k` components
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, k, direction = Direction::Left, include_non_coprime = false,
+    chunk_strategy = ChunkStrategy::Adaptive
+))]
+fn multicurves_with_k_components_upto_complexity(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    k: u64,
+    direction: Direction,
+    include_non_coprime: bool,
+    chunk_strategy: ChunkStrategy,
+) -> PyResult<Vec<(u64, u64)>> {
+    filtered_sweep(
+        py,
+        perm,
+        complexity,
+        |(two_sided, one_sided)| two_sided + one_sided == k,
+        direction,
+        include_non_coprime,
+        chunk_strategy,
+    )
 }
 
 /// A Python module implemented in Rust. The name of this function must match
 /// the `lib.name` setting in the `Cargo.toml`, else Python will not be able to
 /// import the module.
-#[pymodule]
-fn counting_components(_py: Python, m: &PyModule) -> PyResult<()> {
+///
+/// The module does not rely on the GIL: shared state such as the cache and the thread pool is
+/// behind locks, and the counting functions detach from the interpreter while tracing, so that
+/// free-threaded builds of CPython can run counts from several Python threads in parallel.
+#[cfg(feature = "python")]
+#[pymodule(gil_used = false)]
+fn counting_components(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<SignedPermutation>()?;
     m.add_class::<PyStrand>()?;
+    m.add_class::<OrbitIterator>()?;
+    m.add_class::<SignedPermutationIterator>()?;
     m.add_function(wrap_pyfunction!(get_next_major_strand, m)?)?;
+    m.add_function(wrap_pyfunction!(get_previous_major_strand, m)?)?;
+    m.add_function(wrap_pyfunction!(trace_orbit, m)?)?;
     m.add_function(wrap_pyfunction!(has_one_component, m)?)?;
+    m.add_function(wrap_pyfunction!(has_one_component_multi_seed, m)?)?;
+    m.add_function(wrap_pyfunction!(count_components, m)?)?;
     m.add_function(wrap_pyfunction!(count_components_with_orientability, m)?)?;
+    m.add_function(wrap_pyfunction!(component_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(component_labels, m)?)?;
+    m.add_function(wrap_pyfunction!(measured_multicurve, m)?)?;
+    m.add_function(wrap_pyfunction!(neighborhood_boundary_count, m)?)?;
     m.add_function(wrap_pyfunction!(count_components_upto_complexity, m)?)?;
+    m.add_function(wrap_pyfunction!(sweep_to_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(count_components_in_range, m)?)?;
+    m.add_function(wrap_pyfunction!(count_components_shard, m)?)?;
     m.add_function(wrap_pyfunction!(two_sided_multicurves_upto_complexity, m)?)?;
+    m.add_function(wrap_pyfunction!(multicurves_upto_complexity, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        multicurves_with_k_components_upto_complexity,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(count_components_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(extend_sweep, m)?)?;
+    m.add_function(wrap_pyfunction!(component_count_histogram, m)?)?;
+    m.add_function(wrap_pyfunction!(find_smallest_connected, m)?)?;
+    m.add_function(wrap_pyfunction!(connected_density, m)?)?;
+    m.add_function(wrap_pyfunction!(mean_components_by_complexity, m)?)?;
+    m.add_function(wrap_pyfunction!(set_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(get_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(set_debug, m)?)?;
+    if std::env::var_os("COUNTING_COMPONENTS_DEBUG").is_some_and(|value| value != "0") {
+        DEBUG.store(true, Ordering::Relaxed);
+    }
     m.add(
         "PermutationException",
-        _py.get_type::<PermutationException>(),
+        m.py().get_type::<PermutationException>(),
+    )?;
+    m.add(
+        "InvalidPermutationError",
+        m.py().get_type::<InvalidPermutationError>(),
+    )?;
+    m.add(
+        "InvalidFlipsetError",
+        m.py().get_type::<InvalidFlipsetError>(),
+    )?;
+    m.add(
+        "InvalidStrandTypeError",
+        m.py().get_type::<InvalidStrandTypeError>(),
+    )?;
+    m.add(
+        "StepLimitExceededError",
+        m.py().get_type::<StepLimitExceededError>(),
     )?;
+    background::register(m)?;
+    bigons::register(m)?;
+    cache::register(m)?;
+    checkpoint::register(m)?;
+    classify::register(m)?;
+    composite::register(m)?;
+    cover::register(m)?;
+    draw::register(m)?;
+    euclid::register(m)?;
+    farey::register(m)?;
+    graph::register(m)?;
+    fixed_m::register(m)?;
+    homology::register(m)?;
+    iet::register(m)?;
+    intersection::register(m)?;
+    lamination::register(m)?;
+    monte_carlo::register(m)?;
+    oriented::register(m)?;
+    partial::register(m)?;
+    rauzy::register(m)?;
+    regions::register(m)?;
+    results::register(m)?;
+    results_file::register(m)?;
+    sqlite::register(m)?;
+    symmetry::register(m)?;
+    train_track::register(m)?;
+    twist::register(m)?;
+    two_permutation::register(m)?;
+    verify::register(m)?;
 
     Ok(())
 }
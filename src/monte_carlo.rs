@@ -0,0 +1,111 @@
+//! Statistical estimates for sweeps too large to run exhaustively
+//!
+//! Samples are drawn uniformly from the coprime (m,n) pairs of a sweep by rejection, each sample
+//! using its own SplitMix64 stream seeded from its index, so that estimates only depend on the
+//! seed and not on how the samples are spread over the threads.
+
+use crate::{
+    check_sweep_bounds, connected_orientability, run_interruptible, Direction, SignedPermutation,
+    SlotLayout,
+};
+use gcd::Gcd;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// SplitMix64 pseudorandom generator
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Integer in 1..=bound, with a bias of at most bound / 2^64
+    fn between_one_and(&mut self, bound: u64) -> u64 {
+        1 + ((self.next() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+/// Uniformly random (m,n) with m,n > 0, m+n < complexity and gcd(m,n) = 1, for complexity > 2
+fn random_coprime_pair(rng: &mut SplitMix64, complexity: u64) -> (u64, u64) {
+    loop {
+        let m = rng.between_one_and(complexity - 2);
+        let n = rng.between_one_and(complexity - 2);
+        if m + n < complexity && m.gcd_binary(n) == 1 {
+            return (m, n);
+        }
+    }
+}
+
+/// Wilson score interval for `successes` out of `samples` trials at `z` standard deviations
+fn wilson_interval(successes: usize, samples: usize, z: f64) -> (f64, f64) {
+    let (k, n) = (successes as f64, samples as f64);
+    let p = k / n;
+    let denominator = 1.0 + z * z / n;
+    let center = (p + z * z / (2.0 * n)) / denominator;
+    let half_width = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+/// Estimates the proportion of the coprime (m,n) with m+n < complexity whose resolved multicurve
+/// is connected, from `samples` uniformly random pairs checked in parallel. Returns the estimate
+/// and the Wilson score interval at `z` standard deviations, 1.96 for 95% confidence, as
+/// `(estimate, lower, upper)`. The samples are determined by `seed`, which is taken from the
+/// clock if not given.
+#[pyfunction]
+#[pyo3(signature = (perm, complexity, samples, seed = None, z = 1.96, direction = Direction::Left))]
+fn estimate_connected_probability(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    samples: usize,
+    seed: Option<u64>,
+    z: f64,
+    direction: Direction,
+) -> PyResult<(f64, f64, f64)> {
+    if complexity < 3 || samples == 0 {
+        return Err(PyValueError::new_err(
+            "Estimating requires complexity >= 3 and at least one sample",
+        ));
+    }
+    check_sweep_bounds(perm, complexity)?;
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+
+    let connected = run_interruptible(py, |cancelled| {
+        (0..samples as u64)
+            .into_par_iter()
+            .map(|sample| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let mut rng = SplitMix64(seed ^ SplitMix64(sample).next());
+                let (m, n) = random_coprime_pair(&mut rng, complexity);
+                let layout = SlotLayout::Uniform(m);
+                Some(connected_orientability(perm, &layout, n, direction).is_some() as usize)
+            })
+            .try_reduce(|| 0, |a, b| Some(a + b))
+    })?;
+
+    let (lower, upper) = wilson_interval(connected, samples, z);
+    Ok((connected as f64 / samples as f64, lower, upper))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(estimate_connected_probability, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Traversal keeping track of the local orientation
+//!
+//! Every strand is traversed with a direction bit telling whether the local orientation carried
+//! along the component is reversed with respect to the picture, flipping at every flipped
+//! transition. Each original curve carries such a bit as well: the curve of δ through a cycle of
+//! the permutation starts unreversed at the smallest letter of the cycle, and the transverse curve
+//! is unreversed. A component is coherent when it can be oriented so that every one of its strands
+//! runs along the orientation of the original curve it is parallel to, i.e. when its direction bit
+//! relative to that of the original curve is the same all along each original curve.
+
+use crate::{
+    check_strand, next_major_strand, Direction, Multiplicity, PyStrand, SignedPermutation,
+    SlotLayout, Strand, VisitedStrands,
+};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::HashMap;
+
+/// Same as `get_next_major_strand`, taking and returning the direction bit of the strand instead
+/// of whether the transition is flipped
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, strand, reversed, direction = Direction::Left))]
+fn get_next_oriented_strand(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    strand: PyStrand,
+    reversed: bool,
+    direction: Direction,
+) -> PyResult<(PyStrand, bool)> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    check_strand(perm, &layout, n, strand)?;
+    let (next_strand, flipped) = next_major_strand(perm, &layout, n, strand, direction);
+    Ok((next_strand, reversed != (flipped == 1)))
+}
+
+/// For each letter, the cycle of the permutation containing it and the direction bit of the curve
+/// of δ through that cycle when it enters the band of the letter
+fn band_curves(perm: &SignedPermutation) -> Vec<(usize, bool)> {
+    let length = perm.permutation.len();
+    let mut curves: Vec<Option<(usize, bool)>> = vec![None; length];
+    let mut cycle = 0;
+    for start in 0..length {
+        if curves[start].is_some() {
+            continue;
+        }
+        let (mut letter, mut reversed) = (start, false);
+        while curves[letter].is_none() {
+            curves[letter] = Some((cycle, reversed));
+            reversed ^= perm.flipped[letter];
+            letter = perm.permutation[letter];
+        }
+        cycle += 1;
+    }
+    curves.into_iter().map(Option::unwrap).collect()
+}
+
+/// Outputs, for each component, its orbit length, its orientability (0 for two-sided, 1 for
+/// one-sided) and whether it is coherent with the orientations of the original curves, in the
+/// same order as `component_profile`. One-sided components are never coherent.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn component_coherence(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<Vec<(u64, usize, bool)>> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let curves = band_curves(perm);
+    // The transverse curve comes after the cycles of the permutation
+    let transverse_curve = perm.permutation.len();
+    let mut strands = VisitedStrands::new(perm, &layout, n);
+    let mut components = Vec::new();
+
+    while let Some(first_strand) = strands.pop_first() {
+        let mut signs: HashMap<usize, bool> = HashMap::new();
+        let mut coherent = true;
+        let (mut strand, mut reversed, mut length) = (first_strand, false, 0);
+        loop {
+            let (curve, curve_reversed) = match strand.strand {
+                Strand::PermutationDirection(perm_index, _) => curves[perm_index],
+                Strand::Transverse(_) => (transverse_curve, false),
+            };
+            let sign = reversed != curve_reversed;
+            coherent &= *signs.entry(curve).or_insert(sign) == sign;
+            strands.insert(strand);
+            length += 1;
+
+            let (next_strand, flipped) = next_major_strand(perm, &layout, n, strand, direction);
+            reversed ^= flipped == 1;
+            strand = next_strand;
+            if strand == first_strand {
+                break;
+            }
+        }
+        // Coming back reversed means the first strand is also run against its orientation
+        components.push((length, reversed as usize, coherent && !reversed));
+    }
+    Ok(components)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(get_next_oriented_strand, m)?)?;
+    m.add_function(wrap_pyfunction!(component_coherence, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Partial resolution of the crossings
+//!
+//! Inside the annulus, the copies of the bands run up through the n transverse strands, and each
+//! copy crosses each transverse strand once. The chosen crossings are smoothed the same way as in
+//! the full resolution, and the curves go straight through the others, so that the result is an
+//! immersed multicurve whose components are traced crossing by crossing. Smoothing every crossing
+//! gives back the counts of `count_components_with_orientability`.
+
+use crate::results::ComponentCount;
+use crate::{
+    component_counts, narrow, Direction, Multiplicity, SignedPermutation, SlotLayout, Strand,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Side through which a curve arrives at a crossing: from below along a copy of a band, or along
+/// a transverse strand, from the left when turning left and from the right when turning right
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Arrival {
+    Below,
+    Beside,
+}
+
+/// Crossings of the annulus, the one in the column of a top slot and the row of a transverse
+/// strand, counted from the top, being numbered `slot * n + row`
+struct Crossings<'a> {
+    perm: &'a SignedPermutation,
+    layout: &'a SlotLayout,
+    n: u64,
+    slots: u64,
+    direction: Direction,
+    smoothed: Vec<bool>,
+}
+
+impl Crossings<'_> {
+    fn index(&self, slot: u64, row: u64) -> usize {
+        (slot * self.n + row) as usize
+    }
+
+    /// Next crossing met by the curve leaving `(slot, row)`, and whether it comes back flipped
+    fn next(&self, slot: u64, row: u64, arrival: Arrival) -> ((u64, u64, Arrival), usize) {
+        let smoothed = self.smoothed[self.index(slot, row)];
+        let upwards = (arrival == Arrival::Below) != smoothed;
+        if upwards {
+            if row > 0 {
+                return ((slot, row - 1, Arrival::Below), 0);
+            }
+            // Leaves the top of the annulus, and comes back at the bottom through its band
+            let (band, mut copy) = match self.layout.top_strand(slot) {
+                Strand::PermutationDirection(band, copy) => (band, copy),
+                Strand::Transverse(_) => unreachable!(),
+            };
+            let mut flipped = 0;
+            if self.perm.flipped[band] {
+                copy = self.layout.copies(band) - copy - 1;
+                flipped = 1;
+            }
+            let bottom = self.layout.bottom_position(self.perm, band, copy);
+            return ((bottom, self.n - 1, Arrival::Below), flipped);
+        }
+        match self.direction {
+            Direction::Left if slot + 1 < self.slots => ((slot + 1, row, Arrival::Beside), 0),
+            Direction::Right if slot > 0 => ((slot - 1, row, Arrival::Beside), 0),
+            _ => {
+                // Goes around the annulus, reversing the rows if the transverse strands flip
+                let (row, flipped) = if self.perm.transverse_flip {
+                    (self.n - row - 1, 1)
+                } else {
+                    (row, 0)
+                };
+                let slot = match self.direction {
+                    Direction::Left => 0,
+                    Direction::Right => self.slots - 1,
+                };
+                ((slot, row, Arrival::Beside), flipped)
+            }
+        }
+    }
+}
+
+/// Smooths the crossings listed as `(band, copy, transverse)` triples, where `copy` and
+/// `transverse` are indices as in the strands of `get_next_major_strand`, and lets the curves go
+/// straight through the other crossings. Returns the numbers of two-sided and one-sided components
+/// of the resulting immersed multicurve, together with the number of crossings left.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, smoothed, direction = Direction::Left))]
+fn partial_resolution(
+    py: Python,
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    smoothed: Vec<(usize, u64, u64)>,
+    direction: Direction,
+) -> PyResult<(ComponentCount, u64)> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let slots = layout.slots(perm);
+    let total = slots
+        .checked_mul(n)
+        .ok_or_else(|| PyValueError::new_err("The number of crossings does not fit in a u64"))?;
+    let mut crossings = Crossings {
+        perm,
+        layout: &layout,
+        n,
+        slots,
+        direction,
+        smoothed: vec![false; narrow(total)?],
+    };
+    for (band, copy, transverse) in smoothed {
+        if band >= perm.permutation.len() || copy >= layout.copies(band) || transverse >= n {
+            return Err(PyValueError::new_err(format!(
+                "({}, {}, {}) is not a crossing of the configuration",
+                band, copy, transverse
+            )));
+        }
+        let index = crossings.index(layout.top_position(band, copy), n - transverse - 1);
+        crossings.smoothed[index] = true;
+    }
+    let remaining = crossings
+        .smoothed
+        .iter()
+        .filter(|smoothed| !**smoothed)
+        .count() as u64;
+    if total == 0 {
+        // Nothing crosses, so the curves are those of the full resolution
+        return Ok((component_counts(perm, &layout, n, direction).into(), 0));
+    }
+
+    let counts = py.detach(|| {
+        // Crossings reached from each side, indexed as the crossings
+        let mut visited = [vec![false; total as usize], vec![false; total as usize]];
+        let side = |arrival| match arrival {
+            Arrival::Below => 0,
+            Arrival::Beside => 1,
+        };
+        let mut counts = (0, 0);
+        for slot in 0..slots {
+            for row in 0..n {
+                for arrival in [Arrival::Below, Arrival::Beside].iter() {
+                    if visited[side(*arrival)][crossings.index(slot, row)] {
+                        continue;
+                    }
+                    let mut orientability = 0;
+                    let mut current = (slot, row, *arrival);
+                    while !visited[side(current.2)][crossings.index(current.0, current.1)] {
+                        visited[side(current.2)][crossings.index(current.0, current.1)] = true;
+                        let (next, flipped) = crossings.next(current.0, current.1, current.2);
+                        orientability ^= flipped;
+                        current = next;
+                    }
+                    if orientability == 0 {
+                        counts.0 += 1;
+                    } else {
+                        counts.1 += 1;
+                    }
+                }
+            }
+        }
+        counts
+    });
+    Ok((counts.into(), remaining))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(partial_resolution, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! Rauzy induction on signed permutations
+//!
+//! A signed permutation describes an interval exchange with flips: interval j on top is sent to
+//! position perm(j) on the bottom, reversing its orientation if j is flipped. Rauzy induction
+//! compares the last intervals on top and on the bottom, and induces the map on the interval
+//! obtained by cutting off the shorter one. The longer one is the winner, and the loser is moved
+//! next to the image of the winner in the other row, on the side given by the winner's flip.
+
+use crate::{PermutationError, SignedPermutation};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pybacked::PyBackedStr;
+use pyo3::types::PyString;
+use pyo3::wrap_pyfunction;
+use std::collections::{HashSet, VecDeque};
+use std::convert::Infallible;
+
+/// Which row's last interval wins a Rauzy move
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+enum RauzyMove {
+    Top,
+    Bottom,
+}
+
+impl<'a, 'py> FromPyObject<'a, 'py> for RauzyMove {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        match &*obj.extract::<PyBackedStr>()? {
+            "top" => Ok(RauzyMove::Top),
+            "bottom" => Ok(RauzyMove::Bottom),
+            _ => Err(PermutationError::InvalidRauzyMove.into()),
+        }
+    }
+}
+
+impl<'py> IntoPyObject<'py> for RauzyMove {
+    type Target = PyString;
+    type Output = Bound<'py, PyString>;
+    type Error = Infallible;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(PyString::new(
+            py,
+            match self {
+                RauzyMove::Top => "top",
+                RauzyMove::Bottom => "bottom",
+            },
+        ))
+    }
+}
+
+/// Rauzy move on the labels, where `winner` takes the last label of the loser's `row` and puts it
+/// next to itself, after it unless the winner is flipped, returning the moved label
+pub(crate) fn move_loser(row: &mut Vec<usize>, flipped: &mut [bool], winner: usize) -> usize {
+    let loser = row.pop().unwrap();
+    let position = row.iter().position(|label| *label == winner).unwrap();
+    if flipped[winner] {
+        row.insert(position, loser);
+    } else {
+        row.insert(position + 1, loser);
+    }
+    flipped[loser] ^= flipped[winner];
+    loser
+}
+
+/// Labels of the intervals from left to right on top and on the bottom
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct TwoRows {
+    top: Vec<usize>,
+    bottom: Vec<usize>,
+    flipped: Vec<bool>,
+}
+
+impl TwoRows {
+    fn new(perm: &SignedPermutation) -> Self {
+        let length = perm.permutation.len();
+        let mut bottom = vec![0; length];
+        for (label, position) in perm.permutation.iter().enumerate() {
+            bottom[*position] = label;
+        }
+        Self {
+            top: (0..length).collect(),
+            bottom,
+            flipped: (0..length).map(|j| perm.flip_set.contains(&j)).collect(),
+        }
+    }
+
+    /// Labels of the last intervals on top and on the bottom, or `None` if they coincide and no
+    /// Rauzy move is defined
+    fn last_labels(&self) -> Option<(usize, usize)> {
+        match (self.top.last(), self.bottom.last()) {
+            (Some(top), Some(bottom)) if top != bottom => Some((*top, *bottom)),
+            _ => None,
+        }
+    }
+
+    /// Applies a Rauzy move, returning the winning and losing labels
+    fn apply(&mut self, rauzy_move: RauzyMove) -> Option<(usize, usize)> {
+        let (top_label, bottom_label) = self.last_labels()?;
+        let (winner, loser, row) = match rauzy_move {
+            RauzyMove::Top => (top_label, bottom_label, &mut self.bottom),
+            RauzyMove::Bottom => (bottom_label, top_label, &mut self.top),
+        };
+        move_loser(row, &mut self.flipped, winner);
+        Some((winner, loser))
+    }
+
+    /// Relabels the intervals so the top row reads 0 to len - 1, returning the new label of every
+    /// old label along with the resulting signed permutation
+    fn to_permutation(&self, transverse_flip: bool) -> PyResult<(Vec<usize>, SignedPermutation)> {
+        let mut relabel = vec![0; self.top.len()];
+        for (position, label) in self.top.iter().enumerate() {
+            relabel[*label] = position;
+        }
+        let images = self.bottom.iter().map(|label| relabel[*label]).collect();
+        let flips = (0..self.top.len())
+            .filter(|label| self.flipped[*label])
+            .map(|label| relabel[label])
+            .collect();
+        let perm = SignedPermutation::new(images, flips, transverse_flip)?;
+        Ok((relabel, perm))
+    }
+}
+
+/// Applies a Rauzy move, where `rauzy_move` is `'top'` or `'bottom'` depending on which row's
+/// last interval wins. Raises `ValueError` if both rows end with the same interval.
+#[pyfunction]
+fn rauzy_move(perm: &SignedPermutation, rauzy_move: RauzyMove) -> PyResult<SignedPermutation> {
+    let mut rows = TwoRows::new(perm);
+    rows.apply(rauzy_move).ok_or_else(|| {
+        PyValueError::new_err("Rauzy moves require the rows to end with different intervals")
+    })?;
+    Ok(rows.to_permutation(perm.transverse_flip)?.1)
+}
+
+/// Lists the Rauzy class of a signed permutation, i.e. every signed permutation reachable by
+/// Rauzy moves, in breadth first order starting with `perm`
+#[pyfunction]
+fn rauzy_class(perm: &SignedPermutation) -> PyResult<Vec<SignedPermutation>> {
+    let start = TwoRows::new(perm);
+    let mut seen = HashSet::new();
+    seen.insert((start.bottom.clone(), start.flipped.clone()));
+    let mut queue = VecDeque::from(vec![start]);
+    let mut class = Vec::new();
+
+    while let Some(rows) = queue.pop_front() {
+        for rauzy_move in [RauzyMove::Top, RauzyMove::Bottom] {
+            let mut next = rows.clone();
+            if next.apply(rauzy_move).is_none() {
+                continue;
+            }
+            let (_, next_perm) = next.to_permutation(perm.transverse_flip)?;
+            let next = TwoRows::new(&next_perm);
+            if seen.insert((next.bottom.clone(), next.flipped.clone())) {
+                queue.push_back(next);
+            }
+        }
+        class.push(rows.to_permutation(perm.transverse_flip)?.1);
+    }
+    Ok(class)
+}
+
+/// Runs at most `steps` steps of Rauzy induction on the interval exchange with interval lengths
+/// `lengths`, stopping early once the last intervals have equal length. Returns the moves made,
+/// the final signed permutation and the lengths of its intervals.
+#[pyfunction]
+fn rauzy_induction(
+    perm: &SignedPermutation,
+    lengths: Vec<usize>,
+    steps: usize,
+) -> PyResult<(Vec<RauzyMove>, SignedPermutation, Vec<usize>)> {
+    if lengths.len() != perm.permutation.len() {
+        return Err(PyValueError::new_err(format!(
+            "Expected {} lengths, one for each interval, got {}",
+            perm.permutation.len(),
+            lengths.len()
+        )));
+    }
+    let mut rows = TwoRows::new(perm);
+    let mut lengths = lengths;
+    let mut path = Vec::new();
+
+    while path.len() < steps {
+        let (top_label, bottom_label) = match rows.last_labels() {
+            Some(labels) => labels,
+            None => break,
+        };
+        let rauzy_move = match lengths[top_label].cmp(&lengths[bottom_label]) {
+            std::cmp::Ordering::Greater => RauzyMove::Top,
+            std::cmp::Ordering::Less => RauzyMove::Bottom,
+            std::cmp::Ordering::Equal => break,
+        };
+        let (winner, loser) = rows.apply(rauzy_move).unwrap();
+        lengths[winner] -= lengths[loser];
+        path.push(rauzy_move);
+    }
+
+    let (relabel, final_perm) = rows.to_permutation(perm.transverse_flip)?;
+    let mut final_lengths = vec![0; lengths.len()];
+    for (label, length) in lengths.into_iter().enumerate() {
+        final_lengths[relabel[label]] = length;
+    }
+    Ok((path, final_perm, final_lengths))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(rauzy_move, m)?)?;
+    m.add_function(wrap_pyfunction!(rauzy_class, m)?)?;
+    m.add_function(wrap_pyfunction!(rauzy_induction, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,362 @@
+//! Complementary regions of the resolved multicurve
+//!
+//! The regular neighborhood N of the original curves is an annulus around the transverse curve
+//! with one band for each letter of the permutation. Band j leaves the top of the annulus at
+//! block j of the slots, and comes back to the bottom at block perm(j), with a half twist if j is
+//! flipped. The resolved multicurve cuts the annulus into km rectangles, and every band into m + 1
+//! strips. Gluing these disks along the segments of the annulus boundary recovers each region, so
+//! its Euler characteristic is the number of pieces minus the number of gluings.
+
+use crate::{
+    has_one_component, narrow, next_major_strand, strand_count, Direction, Multiplicity, PyStrand,
+    SignedPermutation, SlotLayout, Strand,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Union-find over `0..size`
+pub(crate) struct DisjointSets {
+    parent: Vec<usize>,
+}
+
+impl DisjointSets {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    pub(crate) fn find(&mut self, mut element: usize) -> usize {
+        while self.parent[element] != element {
+            self.parent[element] = self.parent[self.parent[element]];
+            element = self.parent[element];
+        }
+        element
+    }
+
+    pub(crate) fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[a] = b;
+    }
+}
+
+/// A connected component of the complement of the resolved multicurve in N
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub(crate) struct Region {
+    pub(crate) euler_characteristic: isize,
+    /// Boundary cycles running parallel to a side of a component of the multicurve
+    pub(crate) curve_boundaries: usize,
+    /// Boundary cycles that are boundary components of N
+    pub(crate) outer_boundaries: usize,
+}
+
+/// Ends of the bands on the boundary of the annulus: top left, top right, bottom left and bottom
+/// right corner of each block of slots
+fn corner(block: usize, offset: usize) -> usize {
+    4 * block + offset
+}
+
+/// Groups the boundary of N into cycles. Each corner of a band end lies on exactly one gap of
+/// the annulus boundary and one side of a band, so every cycle contains the left or right side
+/// of some band. Returns, for each cycle, a band and whether the cycle runs along its left side.
+/// If the annulus is twisted, the gaps across its seam join the top to the bottom.
+fn outer_boundary_cycles(perm: &SignedPermutation) -> Vec<(usize, bool)> {
+    let length = perm.permutation.len();
+    let (top_left, top_right, bottom_left, bottom_right) = (0, 1, 2, 3);
+    let mut corners = DisjointSets::new(4 * length);
+
+    for block in 0..length {
+        let previous = (block + length - 1) % length;
+        let (top, bottom) = if block == 0 && perm.transverse_flip {
+            (bottom_left, top_left)
+        } else {
+            (top_left, bottom_left)
+        };
+        corners.union(corner(previous, top_right), corner(block, top));
+        corners.union(corner(previous, bottom_right), corner(block, bottom));
+    }
+    for band in 0..length {
+        let bottom = perm.permutation[band];
+        let (left_end, right_end) = if perm.flip_set.contains(&band) {
+            (bottom_right, bottom_left)
+        } else {
+            (bottom_left, bottom_right)
+        };
+        corners.union(corner(band, top_left), corner(bottom, left_end));
+        corners.union(corner(band, top_right), corner(bottom, right_end));
+    }
+
+    let mut seen = vec![false; 4 * length];
+    let mut cycles = Vec::new();
+    for band in 0..length {
+        for (offset, left) in [(top_left, true), (top_right, false)] {
+            let root = corners.find(corner(band, offset));
+            if !seen[root] {
+                seen[root] = true;
+                cycles.push((band, left));
+            }
+        }
+    }
+    cycles
+}
+
+/// Regions when there are no permutation direction strands, so that the multicurve is just n
+/// parallel copies of the transverse curve
+fn regions_without_permutation_strands(perm: &SignedPermutation, n: u64) -> Vec<Region> {
+    let length = perm.permutation.len();
+    let outer_boundaries = if length == 0 {
+        2
+    } else {
+        outer_boundary_cycles(perm).len()
+    };
+    let mut regions = Vec::new();
+
+    if n == 0 {
+        regions.push(Region {
+            euler_characteristic: -(length as isize),
+            curve_boundaries: 0,
+            outer_boundaries,
+        });
+    } else if length == 0 {
+        // The annulus is cut into n + 1 annuli, the outer two of which touch its boundary
+        for _ in 0..2 {
+            regions.push(Region {
+                euler_characteristic: 0,
+                curve_boundaries: 1,
+                outer_boundaries: 1,
+            });
+        }
+    } else {
+        // The bands connect the annuli above the top copy and below the bottom copy
+        regions.push(Region {
+            euler_characteristic: -(length as isize),
+            curve_boundaries: 2,
+            outer_boundaries,
+        });
+    }
+    for _ in 1..n {
+        regions.push(Region {
+            euler_characteristic: 0,
+            curve_boundaries: 2,
+            outer_boundaries: 0,
+        });
+    }
+    regions
+}
+
+/// Rejects configurations where the neighborhood of the transverse curve is a Möbius band
+fn check_untwisted(perm: &SignedPermutation) -> PyResult<()> {
+    if perm.transverse_flip {
+        return Err(PyValueError::new_err(
+            "Complementary regions require an untwisted transverse curve",
+        ));
+    }
+    Ok(())
+}
+
+/// The pieces of the band strands are stored, so `m * len` must fit in a usize, raising
+/// `OverflowError` otherwise
+fn stored_multiplicity(perm: &SignedPermutation, m: u64) -> PyResult<usize> {
+    narrow(strand_count(perm, m, 0)?)?;
+    Ok(m as usize)
+}
+
+/// Unchecked computation of the complementary regions, ordered by their smallest piece
+pub(crate) fn trace_regions(perm: &SignedPermutation, m: usize, n: u64) -> Vec<Region> {
+    let length = perm.permutation.len();
+    let slots = length * m;
+    if slots == 0 {
+        return regions_without_permutation_strands(perm, n);
+    }
+
+    // Pieces 0..slots are the rectangles of the annulus, rectangle a lying between the arc
+    // starting at bottom slot a and the next one. Strip t of band j lies between copies t - 1 and
+    // t of the band, with strips 0 and m running along its sides.
+    let strip = |band: usize, index: usize| slots + band * (m + 1) + index;
+    let piece_count = slots + length * (m + 1);
+    let mut pieces = DisjointSets::new(piece_count);
+    let mut gluings = vec![0isize; piece_count];
+
+    let mut band_ending_at = vec![0; length];
+    for (band, bottom) in perm.permutation.iter().enumerate() {
+        band_ending_at[*bottom] = band;
+    }
+
+    for slot in 0..slots {
+        let (block, copy) = (slot / m, slot % m);
+        let next_block = (block + 1) % length;
+
+        // The top segment after a slot belongs to the rectangle whose arc starts n slots earlier
+        let rectangle = (slot + slots - (n % slots as u64) as usize) % slots;
+        let mut top_strips = vec![strip(block, copy + 1)];
+        if copy == m - 1 {
+            top_strips.push(strip(next_block, 0));
+        }
+        for piece in top_strips {
+            pieces.union(rectangle, piece);
+            gluings[piece] += 1;
+        }
+
+        // The bottom segment after a slot belongs to the rectangle starting at that slot. The
+        // strips attached to it are counted from the other side if their band is flipped.
+        let mut bottom_strips = vec![(band_ending_at[block], copy + 1)];
+        if copy == m - 1 {
+            bottom_strips.push((band_ending_at[next_block], 0));
+        }
+        for (band, index) in bottom_strips {
+            let index = if perm.flip_set.contains(&band) {
+                m - index
+            } else {
+                index
+            };
+            pieces.union(slot, strip(band, index));
+            gluings[strip(band, index)] += 1;
+        }
+    }
+
+    let mut region_of_root = vec![usize::MAX; piece_count];
+    let mut region_of = vec![0; piece_count];
+    let mut regions: Vec<Region> = Vec::new();
+    for piece in 0..piece_count {
+        let root = pieces.find(piece);
+        if region_of_root[root] == usize::MAX {
+            region_of_root[root] = regions.len();
+            regions.push(Region::default());
+        }
+        region_of[piece] = region_of_root[root];
+        regions[region_of[piece]].euler_characteristic += 1 - gluings[piece];
+    }
+
+    // Every component passes through some band, where its two sides border adjacent strips
+    let mut visited = vec![false; slots];
+    for start in 0..slots {
+        if visited[start] {
+            continue;
+        }
+        let (band, copy) = (start / m, start % m);
+        let first_strand = PyStrand {
+            strand: Strand::PermutationDirection(band, copy as u64),
+        };
+        let mut strand = first_strand;
+        let mut orientability = 0;
+        loop {
+            if let Strand::PermutationDirection(j, c) = strand.strand {
+                visited[j * m + c as usize] = true;
+            }
+            let layout = SlotLayout::Uniform(m as u64);
+            let (next_strand, flipped) =
+                next_major_strand(perm, &layout, n, strand, Direction::Left);
+            orientability += flipped;
+            strand = next_strand;
+            if strand == first_strand {
+                break;
+            }
+        }
+
+        regions[region_of[strip(band, copy)]].curve_boundaries += 1;
+        if orientability % 2 == 0 {
+            regions[region_of[strip(band, copy + 1)]].curve_boundaries += 1;
+        }
+    }
+
+    for (band, left) in outer_boundary_cycles(perm) {
+        let piece = if left { strip(band, 0) } else { strip(band, m) };
+        regions[region_of[piece]].outer_boundaries += 1;
+    }
+
+    regions
+}
+
+/// Outputs, for each complementary region of the resolved multicurve in a regular neighborhood
+/// of the original curves, a tuple of its Euler characteristic, the number of its boundary cycles
+/// parallel to the multicurve, and the number of its boundary cycles on the boundary of the
+/// neighborhood
+#[pyfunction]
+fn complementary_regions(
+    perm: &SignedPermutation,
+    m: u64,
+    n: u64,
+) -> PyResult<Vec<(isize, usize, usize)>> {
+    strand_count(perm, m, n)?;
+    check_untwisted(perm)?;
+    Ok(trace_regions(perm, stored_multiplicity(perm, m)?, n)
+        .into_iter()
+        .map(|region| {
+            (
+                region.euler_characteristic,
+                region.curve_boundaries,
+                region.outer_boundaries,
+            )
+        })
+        .collect())
+}
+
+/// Determines whether the resolved curve separates the surface obtained by capping off the
+/// boundary of a regular neighborhood of the original curves. Raises `ValueError` if the
+/// resolution has more than one component.
+#[pyfunction]
+fn is_separating(py: Python, perm: &SignedPermutation, m: u64, n: u64) -> PyResult<bool> {
+    check_untwisted(perm)?;
+    let connected = has_one_component(
+        py,
+        perm,
+        Some(Multiplicity::Uniform(m)),
+        Some(n),
+        Direction::Left,
+        None,
+        None,
+        None,
+    )?
+    .connected;
+    if !connected {
+        return Err(PyValueError::new_err(
+            "is_separating requires a resolution with a single component",
+        ));
+    }
+    // Capping off boundary circles with disks does not change the number of regions
+    Ok(trace_regions(perm, stored_multiplicity(perm, m)?, n).len() > 1)
+}
+
+/// Determines whether the resolved multicurve fills the surface obtained by capping off the
+/// boundary of a regular neighborhood of the original curves, that is whether every
+/// complementary region becomes a disk once its outer boundary cycles are capped
+#[pyfunction]
+fn is_filling(perm: &SignedPermutation, m: u64, n: u64) -> PyResult<bool> {
+    strand_count(perm, m, n)?;
+    check_untwisted(perm)?;
+    Ok(trace_regions(perm, stored_multiplicity(perm, m)?, n)
+        .into_iter()
+        .all(|region| {
+            region.euler_characteristic + region.outer_boundaries as isize == 1
+                && region.curve_boundaries == 1
+        }))
+}
+
+/// Outputs the genus of the surface obtained by capping off the boundary of a regular
+/// neighborhood of the original curves, together with whether it is orientable. The genus of a
+/// non-orientable surface is its number of cross-caps.
+#[pyfunction]
+fn ambient_genus(perm: &SignedPermutation) -> (usize, bool) {
+    let length = perm.permutation.len();
+    let boundaries = match (length, perm.transverse_flip) {
+        (0, false) => 2,
+        (0, true) => 1,
+        _ => outer_boundary_cycles(perm).len(),
+    };
+    // The neighborhood is an annulus or a Möbius band with one band attached for each letter
+    let euler_characteristic = boundaries as isize - length as isize;
+    if perm.flip_set.is_empty() && !perm.transverse_flip {
+        ((2 - euler_characteristic) as usize / 2, true)
+    } else {
+        ((2 - euler_characteristic) as usize, false)
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(complementary_regions, m)?)?;
+    m.add_function(wrap_pyfunction!(is_separating, m)?)?;
+    m.add_function(wrap_pyfunction!(is_filling, m)?)?;
+    m.add_function(wrap_pyfunction!(ambient_genus, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,269 @@
+//! Named result objects
+//!
+//! The results of the component counts are small classes with named attributes, which still
+//! behave like the tuples they replace: they have a length, can be indexed and unpacked, and
+//! compare and hash like the corresponding tuples.
+
+use crate::SweepResult;
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use pyo3::IntoPyObjectExt;
+
+/// Reads a possibly negative tuple index into a tuple of the given length
+pub(crate) fn tuple_index(index: isize, length: usize) -> PyResult<usize> {
+    let position = if index < 0 {
+        index + length as isize
+    } else {
+        index
+    };
+    if (0..length as isize).contains(&position) {
+        Ok(position as usize)
+    } else {
+        Err(PyIndexError::new_err("index out of range"))
+    }
+}
+
+/// Compares the tuple `fields` with `other`, as the object standing for it
+fn tuple_richcmp(
+    fields: Bound<'_, PyTuple>,
+    other: &Bound<'_, PyAny>,
+    op: CompareOp,
+) -> PyResult<Py<PyAny>> {
+    Ok(fields.rich_compare(other, op)?.unbind())
+}
+
+/// Hash of the tuple `fields`, so that equal objects and tuples hash the same
+fn tuple_hash(fields: Bound<'_, PyTuple>) -> PyResult<isize> {
+    fields.hash()
+}
+
+/// Numbers of two-sided and one-sided components, behaving like the tuple `(two_sided, one_sided)`
+#[pyclass]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct ComponentCount {
+    #[pyo3(get)]
+    pub(crate) two_sided: u64,
+    #[pyo3(get)]
+    pub(crate) one_sided: u64,
+}
+
+impl From<(u64, u64)> for ComponentCount {
+    fn from((two_sided, one_sided): (u64, u64)) -> Self {
+        ComponentCount {
+            two_sided,
+            one_sided,
+        }
+    }
+}
+
+impl ComponentCount {
+    fn as_tuple<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        (self.two_sided, self.one_sided).into_pyobject(py)
+    }
+}
+
+#[pymethods]
+impl ComponentCount {
+    /// Total number of components
+    #[getter]
+    fn total(&self) -> u64 {
+        self.two_sided + self.one_sided
+    }
+
+    fn __len__(&self) -> usize {
+        2
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<u64> {
+        Ok([self.two_sided, self.one_sided][tuple_index(index, 2)?])
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "ComponentCount(two_sided={}, one_sided={})",
+            self.two_sided, self.one_sided
+        ))
+    }
+
+    fn __richcmp__(
+        &self,
+        py: Python,
+        other: &Bound<'_, PyAny>,
+        op: CompareOp,
+    ) -> PyResult<Py<PyAny>> {
+        tuple_richcmp(self.as_tuple(py)?, other, op)
+    }
+
+    fn __hash__(&self, py: Python) -> PyResult<isize> {
+        tuple_hash(self.as_tuple(py)?)
+    }
+}
+
+/// An (m,n) pair of a sweep with its component counts, behaving like the tuple
+/// `((m, n), (two_sided, one_sided))`
+#[pyclass]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct SweepEntry {
+    #[pyo3(get)]
+    m: u64,
+    #[pyo3(get)]
+    n: u64,
+    #[pyo3(get)]
+    counts: ComponentCount,
+}
+
+impl From<SweepResult> for SweepEntry {
+    fn from(((m, n), counts): SweepResult) -> Self {
+        SweepEntry {
+            m,
+            n,
+            counts: counts.into(),
+        }
+    }
+}
+
+impl From<SweepEntry> for SweepResult {
+    fn from(entry: SweepEntry) -> Self {
+        let counts = (entry.counts.two_sided, entry.counts.one_sided);
+        ((entry.m, entry.n), counts)
+    }
+}
+
+impl SweepEntry {
+    fn as_tuple<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        let counts = (self.counts.two_sided, self.counts.one_sided);
+        ((self.m, self.n), counts).into_pyobject(py)
+    }
+}
+
+#[pymethods]
+impl SweepEntry {
+    /// Partial quotients of n/m, ending with a quotient of at least 2 unless n/m is an integer,
+    /// and empty for m = 0
+    #[getter]
+    fn continued_fraction(&self) -> Vec<u64> {
+        let mut quotients = Vec::new();
+        let (mut numerator, mut denominator) = (self.n, self.m);
+        while denominator != 0 {
+            quotients.push(numerator / denominator);
+            (numerator, denominator) = (denominator, numerator % denominator);
+        }
+        quotients
+    }
+
+    fn __len__(&self) -> usize {
+        2
+    }
+
+    fn __getitem__(&self, py: Python, index: isize) -> PyResult<Py<PyAny>> {
+        match tuple_index(index, 2)? {
+            0 => (self.m, self.n).into_py_any(py),
+            _ => self.counts.into_py_any(py),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "SweepEntry(m={}, n={}, counts=({}, {}))",
+            self.m, self.n, self.counts.two_sided, self.counts.one_sided
+        ))
+    }
+
+    fn __richcmp__(
+        &self,
+        py: Python,
+        other: &Bound<'_, PyAny>,
+        op: CompareOp,
+    ) -> PyResult<Py<PyAny>> {
+        tuple_richcmp(self.as_tuple(py)?, other, op)
+    }
+
+    fn __hash__(&self, py: Python) -> PyResult<isize> {
+        tuple_hash(self.as_tuple(py)?)
+    }
+}
+
+/// Result of `has_one_component`, behaving like the tuple `(connected, orientability)`
+#[pyclass]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Connectivity {
+    #[pyo3(get)]
+    pub(crate) connected: bool,
+    #[pyo3(get)]
+    pub(crate) orientability: usize,
+}
+
+impl From<(bool, usize)> for Connectivity {
+    fn from((connected, orientability): (bool, usize)) -> Self {
+        Connectivity {
+            connected,
+            orientability,
+        }
+    }
+}
+
+impl Connectivity {
+    fn as_tuple<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        (self.connected, self.orientability).into_pyobject(py)
+    }
+}
+
+#[pymethods]
+impl Connectivity {
+    fn __len__(&self) -> usize {
+        2
+    }
+
+    fn __getitem__(&self, py: Python, index: isize) -> PyResult<Py<PyAny>> {
+        match tuple_index(index, 2)? {
+            0 => self.connected.into_py_any(py),
+            _ => self.orientability.into_py_any(py),
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        let connected = if self.connected { "True" } else { "False" };
+        Ok(format!(
+            "Connectivity(connected={}, orientability={})",
+            connected, self.orientability
+        ))
+    }
+
+    fn __richcmp__(
+        &self,
+        py: Python,
+        other: &Bound<'_, PyAny>,
+        op: CompareOp,
+    ) -> PyResult<Py<PyAny>> {
+        tuple_richcmp(self.as_tuple(py)?, other, op)
+    }
+
+    fn __hash__(&self, py: Python) -> PyResult<isize> {
+        tuple_hash(self.as_tuple(py)?)
+    }
+}
+
+/// Reads sweep results given either as `SweepEntry` objects or as tuples
+pub(crate) fn extract_results(results: Vec<Bound<'_, PyAny>>) -> PyResult<Vec<SweepResult>> {
+    results
+        .into_iter()
+        .map(|result| match result.extract::<SweepEntry>() {
+            Ok(entry) => Ok(entry.into()),
+            Err(_) => result.extract::<SweepResult>(),
+        })
+        .collect()
+}
+
+/// Converts sweep results to `SweepEntry` objects
+pub(crate) fn entries(results: Vec<SweepResult>) -> Vec<SweepEntry> {
+    results.into_iter().map(SweepEntry::from).collect()
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ComponentCount>()?;
+    m.add_class::<Connectivity>()?;
+    m.add_class::<SweepEntry>()?;
+    Ok(())
+}
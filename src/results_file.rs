@@ -0,0 +1,273 @@
+//! Compact binary files of sweep results
+//!
+//! A results file is a sequence of little-endian u64 words: the magic `CCRESv01`, the length of
+//! the permutation followed by the constructor argument of `SignedPermutation`, the number of
+//! flips followed by the sorted flips, the transverse flip (0 or 1) and the number of records.
+//! Then come the records, four words `m, n, two_sided, one_sided` each. Files are read through a
+//! memory map, so that opening one is immediate and only the records accessed are loaded.
+
+use crate::results::{extract_results, tuple_index, SweepEntry};
+use crate::{SignedPermutation, SweepResult};
+use memmap2::Mmap;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Arc;
+
+create_exception!(counting_components, ResultsFileException, PyException);
+
+/// First word of every results file
+const MAGIC: [u8; 8] = *b"CCRESv01";
+
+/// Size in bytes of a record
+const RECORD_SIZE: usize = 32;
+
+/// Enum describing possible errors when reading a results file
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ResultsFileError {
+    /// Header is missing or malformed
+    InvalidHeader,
+    /// File size does not match the number of records in the header
+    SizeMismatch { expected: u64, found: u64 },
+    /// File records the results of a different permutation
+    PermutationMismatch,
+}
+
+impl std::convert::From<ResultsFileError> for PyErr {
+    fn from(err: ResultsFileError) -> PyErr {
+        match err {
+            ResultsFileError::InvalidHeader => {
+                ResultsFileException::new_err("Invalid results file header")
+            }
+            ResultsFileError::SizeMismatch { expected, found } => {
+                ResultsFileException::new_err(format!(
+                    "Results file has {} bytes instead of the {} announced by its header",
+                    found, expected
+                ))
+            }
+            ResultsFileError::PermutationMismatch => {
+                ResultsFileException::new_err("Results file belongs to a different permutation")
+            }
+        }
+    }
+}
+
+fn write_word(file: &mut impl Write, word: u64) -> io::Result<()> {
+    file.write_all(&word.to_le_bytes())
+}
+
+/// Words of the header describing `perm`, without the magic and the number of records
+fn header_words(perm: &SignedPermutation) -> Vec<u64> {
+    // The file stores the constructor argument, which is the inverse of the stored permutation
+    let mut images = vec![0; perm.permutation.len()];
+    for (input, output) in perm.permutation.iter().enumerate() {
+        images[*output] = input as u64;
+    }
+    let mut flips: Vec<u64> = perm.flip_set.iter().map(|&flip| flip as u64).collect();
+    flips.sort_unstable();
+
+    let mut words = vec![images.len() as u64];
+    words.extend(images);
+    words.push(flips.len() as u64);
+    words.extend(flips);
+    words.push(perm.transverse_flip as u64);
+    words
+}
+
+/// Memory-mapped records of a results file
+struct Records {
+    map: Mmap,
+    offset: usize,
+    count: usize,
+}
+
+impl Records {
+    fn word(&self, position: usize) -> u64 {
+        let bytes = &self.map[position..position + 8];
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn get(&self, index: usize) -> SweepResult {
+        let start = self.offset + index * RECORD_SIZE;
+        let [m, n, two_sided, one_sided] = [0, 1, 2, 3].map(|k| self.word(start + 8 * k));
+        ((m, n), (two_sided, one_sided))
+    }
+}
+
+/// Reads the header of a results file, returning the permutation and the position of the first
+/// record with the number of records
+fn read_header(map: &[u8]) -> PyResult<(SignedPermutation, usize, usize)> {
+    if map.get(..8) != Some(&MAGIC[..]) {
+        return Err(ResultsFileError::InvalidHeader.into());
+    }
+    let mut words = map
+        .chunks_exact(8)
+        .skip(1)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    let mut next = || {
+        words
+            .next()
+            .and_then(|word| usize::try_from(word).ok())
+            .ok_or(ResultsFileError::InvalidHeader)
+    };
+    let length = next()?;
+    let images = (0..length).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+    let flip_count = next()?;
+    let flips = (0..flip_count)
+        .map(|_| next())
+        .collect::<Result<Vec<_>, _>>()?;
+    let transverse_flip = match next()? {
+        0 => false,
+        1 => true,
+        _ => return Err(ResultsFileError::InvalidHeader.into()),
+    };
+    let count = next()?;
+    let perm = SignedPermutation::new(images, flips, transverse_flip)?;
+
+    let offset = 8 * (length + flip_count + 5);
+    let expected = count
+        .checked_mul(RECORD_SIZE)
+        .and_then(|size| size.checked_add(offset))
+        .ok_or(ResultsFileError::InvalidHeader)?;
+    if map.len() != expected {
+        return Err(ResultsFileError::SizeMismatch {
+            expected: expected as u64,
+            found: map.len() as u64,
+        }
+        .into());
+    }
+    Ok((perm, offset, count))
+}
+
+/// Writes `results` to a results file at `path` whose header describes `perm`
+pub(crate) fn write_results_file(
+    path: &str,
+    perm: &SignedPermutation,
+    results: &[SweepResult],
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_all(&MAGIC)?;
+    for word in header_words(perm) {
+        write_word(&mut file, word)?;
+    }
+    write_word(&mut file, results.len() as u64)?;
+    for &((m, n), (two_sided, one_sided)) in results {
+        for word in [m, n, two_sided, one_sided] {
+            write_word(&mut file, word)?;
+        }
+    }
+    file.flush()
+}
+
+/// Reads all the records of the results file at `path`, checking that it belongs to `perm`
+pub(crate) fn read_results_file(
+    path: &str,
+    perm: &SignedPermutation,
+) -> PyResult<Vec<SweepResult>> {
+    let file = ResultsFile::new(path)?;
+    if file.perm != *perm {
+        return Err(ResultsFileError::PermutationMismatch.into());
+    }
+    Ok((0..file.records.count)
+        .map(|index| file.records.get(index))
+        .collect())
+}
+
+/// Writes `results`, a list of sweep results as returned by `count_components_upto_complexity`,
+/// to a results file at `path` whose header describes `perm`
+#[pyfunction]
+fn write_results(
+    path: &str,
+    perm: &SignedPermutation,
+    results: Vec<Bound<'_, PyAny>>,
+) -> PyResult<()> {
+    write_results_file(path, perm, &extract_results(results)?)?;
+    Ok(())
+}
+
+/// Results file written by `write_results`, opened via `ResultsFile(path)`
+#[pyclass]
+struct ResultsFile {
+    records: Arc<Records>,
+    #[pyo3(get)]
+    perm: SignedPermutation,
+}
+
+#[pymethods]
+impl ResultsFile {
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        let file = File::open(path)?;
+        // Safety: the map is only read, and results files are not meant to be modified in place
+        let map = unsafe { Mmap::map(&file)? };
+        let (perm, offset, count) = read_header(&map)?;
+        Ok(ResultsFile {
+            records: Arc::new(Records { map, offset, count }),
+            perm,
+        })
+    }
+
+    /// Returns all the records as a list of `SweepEntry`
+    fn to_list(&self) -> Vec<SweepEntry> {
+        (0..self.records.count)
+            .map(|index| self.records.get(index).into())
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.records.count
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<SweepEntry> {
+        let index = tuple_index(index, self.records.count)?;
+        Ok(self.records.get(index).into())
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> ResultsFileIterator {
+        ResultsFileIterator {
+            records: Arc::clone(&slf.records),
+            index: 0,
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("ResultsFile({} records)", self.records.count))
+    }
+}
+
+/// Iterator over the records of a `ResultsFile`
+#[pyclass]
+struct ResultsFileIterator {
+    records: Arc<Records>,
+    index: usize,
+}
+
+#[pymethods]
+impl ResultsFileIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> Option<SweepEntry> {
+        if slf.index == slf.records.count {
+            return None;
+        }
+        let result = slf.records.get(slf.index);
+        slf.index += 1;
+        Some(result.into())
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ResultsFile>()?;
+    m.add_function(wrap_pyfunction!(write_results, m)?)?;
+    m.add(
+        "ResultsFileException",
+        m.py().get_type::<ResultsFileException>(),
+    )?;
+    Ok(())
+}
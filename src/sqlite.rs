@@ -0,0 +1,130 @@
+//! Sweeps streamed into a SQLite database
+//!
+//! Results are written to the table `components(permutation_hash, m, n, two_sided, one_sided)`
+//! as they are computed, one transaction per batch, so that sweeps too large to hold in memory
+//! can be queried with SQL afterwards. `permutation_hash` is the same stable hash of the
+//! permutation, flips and transverse flip as in the checkpoint files, stored as a signed integer.
+
+use crate::cache::cached_component_counts;
+use crate::checkpoint::permutation_hash;
+use crate::{
+    check_sweep_bounds, run_interruptible, sweep_pairs_upto, Direction, SignedPermutation,
+    SweepResult,
+};
+use pyo3::exceptions::PyIOError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use rusqlite::{params, Connection};
+use std::convert::TryFrom;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread;
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS components (
+    permutation_hash INTEGER NOT NULL,
+    m INTEGER NOT NULL,
+    n INTEGER NOT NULL,
+    two_sided INTEGER NOT NULL,
+    one_sided INTEGER NOT NULL,
+    PRIMARY KEY (permutation_hash, m, n)
+)";
+
+fn sqlite_error(err: rusqlite::Error) -> PyErr {
+    PyIOError::new_err(format!("SQLite error: {}", err))
+}
+
+/// SQLite integers are signed 64-bit
+fn integer(value: u64) -> rusqlite::Result<i64> {
+    i64::try_from(value).map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))
+}
+
+/// Writes the results received from `receiver` into `connection`, committing every `batch_size`
+/// rows
+fn write_results(
+    connection: &mut Connection,
+    hash: i64,
+    receiver: mpsc::Receiver<SweepResult>,
+    batch_size: usize,
+) -> rusqlite::Result<()> {
+    let mut results = receiver.into_iter().peekable();
+    while results.peek().is_some() {
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO components (permutation_hash, m, n, two_sided, one_sided)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for ((m, n), (two_sided, one_sided)) in results.by_ref().take(batch_size) {
+                insert.execute(params![
+                    hash,
+                    integer(m)?,
+                    integer(n)?,
+                    integer(two_sided)?,
+                    integer(one_sided)?
+                ])?;
+            }
+        }
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+/// Same as `count_components_upto_complexity`, but streams the results into the SQLite database
+/// at `db_path` as they are computed, committing every `batch_size` rows, instead of returning
+/// them. Creates the database and the `components` table if needed, replacing the rows of pairs
+/// already present. Returns the permutation hash the rows are stored under.
+#[pyfunction]
+#[pyo3(signature = (
+    perm, complexity, db_path, direction = Direction::Left, include_non_coprime = false,
+    batch_size = 1000
+))]
+fn sweep_to_sqlite(
+    py: Python,
+    perm: &SignedPermutation,
+    complexity: u64,
+    db_path: &str,
+    direction: Direction,
+    include_non_coprime: bool,
+    batch_size: usize,
+) -> PyResult<i64> {
+    check_sweep_bounds(perm, complexity)?;
+    let hash = permutation_hash(perm) as i64;
+    let mut connection = Connection::open(db_path).map_err(sqlite_error)?;
+    connection.execute(CREATE_TABLE, []).map_err(sqlite_error)?;
+    let batch_size = batch_size.max(1);
+
+    run_interruptible(py, |cancelled| {
+        let (sender, receiver) = mpsc::channel::<SweepResult>();
+        let written = thread::scope(|scope| {
+            let writer = scope.spawn(|| write_results(&mut connection, hash, receiver, batch_size));
+
+            sweep_pairs_upto(complexity, include_non_coprime).for_each_with(
+                sender,
+                |sender, (m, n)| {
+                    if !cancelled.load(Ordering::Relaxed) {
+                        let counts = cached_component_counts(perm, m, n, direction);
+                        // The writer only hangs up after an SQLite error, which is reported below
+                        let _ = sender.send(((m, n), counts));
+                    }
+                },
+            );
+
+            writer
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        });
+        if cancelled.load(Ordering::Relaxed) {
+            None
+        } else {
+            Some(written)
+        }
+    })?
+    .map_err(sqlite_error)?;
+    Ok(hash)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(sweep_to_sqlite, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,1005 @@
+//! Pure-Rust core of the surgery
+//!
+//! Signed permutations, the strands of a configuration and the transitions between them, with no
+//! dependency on the Python bindings, so that the counting can also be built on its own, e.g. for
+//! WebAssembly. The Python classes `SignedPermutation` and `PyStrand` are defined here and get
+//! their methods in the crate root when the `python` feature is enabled.
+
+// Parts of the core are only used by the Python bindings
+#![cfg_attr(not(feature = "python"), allow(dead_code))]
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Offending entry of an invalid permutation or flip set, as far as it is known: its position in
+/// the input, its value and the number of letters it should be less than
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Offending {
+    pub(crate) index: Option<usize>,
+    pub(crate) value: Option<usize>,
+    pub(crate) length: Option<usize>,
+}
+
+impl Offending {
+    pub(crate) fn new(index: Option<usize>, value: usize, length: usize) -> Self {
+        Offending {
+            index,
+            value: Some(value),
+            length: Some(length),
+        }
+    }
+}
+
+/// Lists the known data, e.g. `index 1, value 3, length 3`
+impl fmt::Display for Offending {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut details = Vec::new();
+        if let Some(index) = self.index {
+            details.push(format!("index {}", index));
+        }
+        if let Some(value) = self.value {
+            details.push(format!("value {}", value));
+        }
+        if let Some(length) = self.length {
+            details.push(format!("length {}", length));
+        }
+        write!(f, "{}", details.join(", "))
+    }
+}
+
+/// Enum describing possible errors when creating a signed permutation or multiple strands
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PermutationError {
+    /// Not a valid permutation on {0,1,...,n-1}
+    InvalidPermutation(Offending),
+    /// Flipset not a subset of {0,1,...,n-1}
+    InvalidFlipset(Offending),
+    /// Strand must be Transverse ('t') or a PermutationDirection ('p')
+    InvalidStrandType(char),
+    /// Direction must be 'left' or 'right'
+    InvalidDirection,
+    /// Rauzy move must be 'top' or 'bottom'
+    InvalidRauzyMove,
+    /// Cycle notation could not be parsed
+    InvalidCycleNotation,
+    /// Orientability filter must be 'all_two_sided', 'all_one_sided' or 'mixed'
+    InvalidOrientabilityFilter,
+    /// Chunk strategy must be 'adaptive' or 'cost'
+    InvalidChunkStrategy,
+}
+
+impl PermutationError {
+    /// Invalid permutation without any offending data, e.g. when it could not be parsed
+    pub(crate) fn malformed_permutation() -> Self {
+        PermutationError::InvalidPermutation(Offending::default())
+    }
+}
+
+impl fmt::Display for PermutationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (message, offending) = match self {
+            PermutationError::InvalidPermutation(offending) => ("Invalid permutation", offending),
+            PermutationError::InvalidFlipset(offending) => ("Invalid flip set", offending),
+            PermutationError::InvalidStrandType(value) => {
+                return write!(f, "Invalid strand type {:?}: only 't' and 'p' allowed", value)
+            }
+            PermutationError::InvalidDirection => {
+                return write!(f, "Invalid direction: only 'left' and 'right' allowed")
+            }
+            PermutationError::InvalidRauzyMove => {
+                return write!(f, "Invalid Rauzy move: only 'top' and 'bottom' allowed")
+            }
+            PermutationError::InvalidCycleNotation => return write!(f, "Invalid cycle notation"),
+            PermutationError::InvalidOrientabilityFilter => {
+                return write!(
+                    f,
+                    "Invalid orientability filter: only 'all_two_sided', 'all_one_sided' and 'mixed' allowed"
+                )
+            }
+            PermutationError::InvalidChunkStrategy => {
+                return write!(f, "Invalid chunk strategy: only 'adaptive' and 'cost' allowed")
+            }
+        };
+        if *offending == Offending::default() {
+            write!(f, "{}", message)
+        } else {
+            write!(f, "{} ({})", message, offending)
+        }
+    }
+}
+
+/// Enum describing possible errors when laying out the strands of a configuration
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LayoutError {
+    /// There must be one multiplicity for each letter of the permutation
+    MultiplicityCount { expected: usize, found: usize },
+    /// Number of strands `m * len + n` does not fit in a u64
+    Overflow { m: u64, n: u64, length: usize },
+    /// Number of strands `sum(m) + n` does not fit in a u64
+    PerBandOverflow { copies: Vec<u64>, n: u64 },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutError::MultiplicityCount { expected, found } => write!(
+                f,
+                "Expected {} multiplicities, one for each letter of the permutation, got {}",
+                expected, found
+            ),
+            LayoutError::Overflow { m, n, length } => write!(
+                f,
+                "Number of strands m * len + n overflows for m = {}, n = {}, len = {}",
+                m, n, length
+            ),
+            LayoutError::PerBandOverflow { copies, n } => write!(
+                f,
+                "Number of strands sum(m) + n overflows for m = {:?}, n = {}",
+                copies, n
+            ),
+        }
+    }
+}
+
+/// An orbit ran for more strands than the limit it was traced with
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StepLimitExceeded {
+    pub(crate) max_steps: u64,
+}
+
+impl fmt::Display for StepLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Orbit exceeded the limit of {} steps", self.max_steps)
+    }
+}
+
+/// Fails once an orbit has taken `steps` steps, more than `max_steps`
+fn check_steps(steps: u64, max_steps: Option<u64>) -> Result<(), StepLimitExceeded> {
+    match max_steps {
+        Some(max_steps) if steps > max_steps => Err(StepLimitExceeded { max_steps }),
+        _ => Ok(()),
+    }
+}
+
+/// Result of a traversal without a step limit, which cannot exceed it
+fn unbounded<T>(result: Result<T, StepLimitExceeded>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(_) => unreachable!("traversal without a step limit exceeded it"),
+    }
+}
+
+/// Permutation and flip data
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignedPermutation {
+    pub(crate) permutation: Vec<usize>,
+    pub(crate) flip_set: HashSet<usize>,
+    /// Whether the transverse strands come back with the local orientation flipped
+    pub(crate) transverse_flip: bool,
+    /// Same as `flip_set`, indexed by letter for the strand stepping
+    pub(crate) flipped: Vec<bool>,
+}
+
+impl SignedPermutation {
+    /// Builds the signed permutation sending each letter j to `permutation[j]`, filling in the
+    /// flips indexed by letter
+    pub(crate) fn from_parts(
+        permutation: Vec<usize>,
+        flip_set: HashSet<usize>,
+        transverse_flip: bool,
+    ) -> Self {
+        let flipped = (0..permutation.len())
+            .map(|letter| flip_set.contains(&letter))
+            .collect();
+        Self {
+            permutation,
+            flip_set,
+            transverse_flip,
+            flipped,
+        }
+    }
+
+    /// Builds the signed permutation from the same arguments as the Python constructor, where
+    /// `permutation[j]` is the letter sent to j
+    pub(crate) fn try_new(
+        permutation: Vec<usize>,
+        flips: Vec<usize>,
+        transverse_flip: bool,
+    ) -> Result<Self, PermutationError> {
+        let length = permutation.len();
+        let mut perm_vector = vec![length; length];
+        let mut flip_set = HashSet::new();
+
+        for (index, value) in permutation.into_iter().enumerate() {
+            // Out of range, or appearing twice
+            if value >= length || perm_vector[value] != length {
+                let offending = Offending::new(Some(index), value, length);
+                return Err(PermutationError::InvalidPermutation(offending));
+            }
+            perm_vector[value] = index;
+        }
+
+        for (index, value) in flips.into_iter().enumerate() {
+            if value >= length {
+                let offending = Offending::new(Some(index), value, length);
+                return Err(PermutationError::InvalidFlipset(offending));
+            }
+            flip_set.insert(value);
+        }
+
+        Ok(Self::from_parts(perm_vector, flip_set, transverse_flip))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub(crate) enum Strand {
+    Transverse(u64),
+    PermutationDirection(usize, u64),
+}
+
+/// Python class to represent a strand
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub(crate) struct PyStrand {
+    pub(crate) strand: Strand,
+}
+
+/// Which way a strand turns when resolving a crossing, looking along the transverse curve
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) enum Direction {
+    Left,
+    Right,
+}
+
+impl FromStr for Direction {
+    type Err = PermutationError;
+
+    fn from_str(direction: &str) -> Result<Self, Self::Err> {
+        match direction {
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            _ => Err(PermutationError::InvalidDirection),
+        }
+    }
+}
+
+/// Number of parallel copies of the permutation direction strands, given either as a single
+/// multiplicity or as one multiplicity for each letter of the permutation
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum Multiplicity {
+    Uniform(u64),
+    PerBand(Vec<u64>),
+}
+
+impl Multiplicity {
+    /// Multiplicity of every letter of a permutation of the given length, failing if there is
+    /// not one for each letter
+    pub(crate) fn per_band(self, length: usize) -> Result<Vec<u64>, LayoutError> {
+        match self {
+            Multiplicity::Uniform(m) => Ok(vec![m; length]),
+            Multiplicity::PerBand(copies) if copies.len() == length => Ok(copies),
+            Multiplicity::PerBand(copies) => Err(LayoutError::MultiplicityCount {
+                expected: length,
+                found: copies.len(),
+            }),
+        }
+    }
+}
+
+/// Positions of the permutation direction strands on the circle of slots. The copies of band j
+/// take up block j at the top of the annulus and block perm(j) at the bottom, so with varying
+/// multiplicities the blocks are laid out differently at the top and at the bottom.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum SlotLayout {
+    Uniform(u64),
+    PerBand {
+        copies: Vec<u64>,
+        top_offsets: Vec<u64>,
+        bottom_offsets: Vec<u64>,
+        slots: u64,
+    },
+}
+
+impl SlotLayout {
+    /// Lays out the multiplicities `m`, failing if there is not one for each letter of the
+    /// permutation or if the number of strands does not fit in a u64
+    pub(crate) fn new(
+        perm: &SignedPermutation,
+        m: Multiplicity,
+        n: u64,
+    ) -> Result<Self, LayoutError> {
+        let copies = match m {
+            Multiplicity::Uniform(m) => {
+                strand_count(perm, m, n)?;
+                return Ok(SlotLayout::Uniform(m));
+            }
+            m => m.per_band(perm.permutation.len())?,
+        };
+        let length = copies.len();
+
+        let overflow = || LayoutError::PerBandOverflow {
+            copies: copies.clone(),
+            n,
+        };
+        let mut top_offsets = Vec::with_capacity(length);
+        let mut slots: u64 = 0;
+        for count in &copies {
+            top_offsets.push(slots);
+            slots = slots.checked_add(*count).ok_or_else(overflow)?;
+        }
+        slots.checked_add(n).ok_or_else(overflow)?;
+
+        let mut band_ending_at = vec![0; length];
+        for (band, bottom) in perm.permutation.iter().enumerate() {
+            band_ending_at[*bottom] = band;
+        }
+        let mut bottom_offsets = Vec::with_capacity(length);
+        let mut offset = 0;
+        for band in band_ending_at {
+            bottom_offsets.push(offset);
+            offset += copies[band];
+        }
+
+        Ok(SlotLayout::PerBand {
+            copies,
+            top_offsets,
+            bottom_offsets,
+            slots,
+        })
+    }
+
+    /// Number of copies running through a band
+    pub(crate) fn copies(&self, band: usize) -> u64 {
+        match self {
+            SlotLayout::Uniform(m) => *m,
+            SlotLayout::PerBand { copies, .. } => copies[band],
+        }
+    }
+
+    /// Number of permutation direction strands
+    pub(crate) fn slots(&self, perm: &SignedPermutation) -> u64 {
+        match self {
+            SlotLayout::Uniform(m) => perm.permutation.len() as u64 * m,
+            SlotLayout::PerBand { slots, .. } => *slots,
+        }
+    }
+
+    /// Slot at the bottom of the annulus where a copy of a band comes back
+    pub(crate) fn bottom_position(&self, perm: &SignedPermutation, band: usize, copy: u64) -> u64 {
+        match self {
+            SlotLayout::Uniform(m) => m * perm.permutation[band] as u64 + copy,
+            SlotLayout::PerBand { bottom_offsets, .. } => {
+                bottom_offsets[perm.permutation[band]] + copy
+            }
+        }
+    }
+
+    /// Permutation direction strand leaving the top of the annulus at a slot
+    pub(crate) fn top_strand(&self, position: u64) -> Strand {
+        match self {
+            SlotLayout::Uniform(m) => {
+                Strand::PermutationDirection((position / m) as usize, position % m)
+            }
+            SlotLayout::PerBand { top_offsets, .. } => {
+                // Bands without copies share their offset with the next band, so take the last
+                let band = top_offsets.partition_point(|offset| *offset <= position) - 1;
+                Strand::PermutationDirection(band, position - top_offsets[band])
+            }
+        }
+    }
+
+    /// Slot at the top of the annulus where a copy of a band leaves
+    pub(crate) fn top_position(&self, band: usize, copy: u64) -> u64 {
+        match self {
+            SlotLayout::Uniform(m) => m * band as u64 + copy,
+            SlotLayout::PerBand { top_offsets, .. } => top_offsets[band] + copy,
+        }
+    }
+
+    /// Band and copy of the permutation direction strand coming back to the bottom of the annulus
+    /// at a slot, before undoing its flip
+    pub(crate) fn bottom_strand(&self, perm: &SignedPermutation, position: u64) -> (usize, u64) {
+        let (block, copy) = match self {
+            SlotLayout::Uniform(m) => ((position / m) as usize, position % m),
+            SlotLayout::PerBand { bottom_offsets, .. } => {
+                let block = bottom_offsets.partition_point(|offset| *offset <= position) - 1;
+                (block, position - bottom_offsets[block])
+            }
+        };
+        let band = perm
+            .permutation
+            .iter()
+            .position(|bottom| *bottom == block)
+            .unwrap();
+        (band, copy)
+    }
+
+    /// Whether the strand is one of the strands with n transverse strands
+    pub(crate) fn contains(&self, perm: &SignedPermutation, n: u64, strand: PyStrand) -> bool {
+        match strand.strand {
+            Strand::Transverse(index) => index < n,
+            Strand::PermutationDirection(band, copy) => {
+                band < perm.permutation.len() && copy < self.copies(band)
+            }
+        }
+    }
+
+    /// Position of the strand in the order of `strands`
+    pub(crate) fn strand_index(&self, n: u64, strand: PyStrand) -> u64 {
+        match strand.strand {
+            Strand::Transverse(index) => index,
+            Strand::PermutationDirection(band, copy) => n + self.top_position(band, copy),
+        }
+    }
+
+    /// Every strand, transverse strands first
+    pub(crate) fn strands(
+        &self,
+        perm: &SignedPermutation,
+        n: u64,
+    ) -> impl Iterator<Item = PyStrand> + '_ {
+        let transverse = (0..n).map(Strand::Transverse);
+        let permutation_direction = (0..perm.permutation.len()).flat_map(move |band| {
+            (0..self.copies(band)).map(move |copy| Strand::PermutationDirection(band, copy))
+        });
+        transverse
+            .chain(permutation_direction)
+            .map(|strand| PyStrand { strand })
+    }
+}
+
+/// Set of visited strands stored as one bit per strand, the strands being numbered in the order
+/// of `SlotLayout::strands`, with a cursor on the smallest strand which might not be visited
+pub(crate) struct VisitedStrands<'a> {
+    layout: &'a SlotLayout,
+    n: u64,
+    words: Vec<u64>,
+    cursor: u64,
+    len: u64,
+}
+
+impl<'a> VisitedStrands<'a> {
+    /// No strand visited yet
+    pub(crate) fn new(perm: &SignedPermutation, layout: &'a SlotLayout, n: u64) -> Self {
+        let len = layout.slots(perm) + n;
+        VisitedStrands {
+            layout,
+            n,
+            words: vec![0; (len / 64 + 1) as usize],
+            cursor: 0,
+            len,
+        }
+    }
+
+    /// Marks the strand as visited
+    pub(crate) fn insert(&mut self, strand: PyStrand) {
+        let index = self.layout.strand_index(self.n, strand);
+        self.words[(index / 64) as usize] |= 1 << (index % 64);
+    }
+
+    /// Smallest strand not visited yet, if any, marking it as visited
+    pub(crate) fn pop_first(&mut self) -> Option<PyStrand> {
+        loop {
+            if self.cursor >= self.len {
+                return None;
+            }
+            let word = self.words[(self.cursor / 64) as usize] >> (self.cursor % 64);
+            if word & 1 == 0 {
+                break;
+            }
+            // Skip the run of visited strands starting at the cursor
+            self.cursor += word.trailing_ones() as u64;
+        }
+        let index = self.cursor;
+        self.words[(index / 64) as usize] |= 1 << (index % 64);
+        self.cursor += 1;
+        let strand = if index < self.n {
+            Strand::Transverse(index)
+        } else {
+            self.layout.top_strand(index - self.n)
+        };
+        Some(PyStrand { strand })
+    }
+}
+
+/// Total number of strands `m * len + n`, failing if it does not fit in a u64. Once this
+/// succeeds, none of the index arithmetic in `next_major_strand` can overflow.
+pub(crate) fn strand_count(perm: &SignedPermutation, m: u64, n: u64) -> Result<u64, LayoutError> {
+    let length = perm.permutation.len();
+    (length as u64)
+        .checked_mul(m)
+        .and_then(|count| count.checked_add(n))
+        .ok_or(LayoutError::Overflow { m, n, length })
+}
+
+/// Whether every transition computed by `next_major_strand` is logged to stderr, initially set
+/// from the `COUNTING_COMPONENTS_DEBUG` environment variable when the module is loaded
+pub(crate) static DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Unchecked version of `get_next_major_strand` for use in traversals, which must validate
+/// `(m, n)` with `SlotLayout::new` or `strand_count` beforehand
+pub(crate) fn next_major_strand(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    strand: PyStrand,
+    direction: Direction,
+) -> (PyStrand, usize) {
+    let debug = DEBUG.load(Ordering::Relaxed);
+    let slots = layout.slots(perm);
+    let mut flipped = 0;
+    let out_strand: PyStrand = match strand.strand {
+        Strand::PermutationDirection(perm_index, mut copy_index) => {
+            if perm.flipped[perm_index] {
+                copy_index = layout.copies(perm_index) - copy_index - 1;
+                flipped = 1;
+            }
+
+            let mut absolute_index = layout.bottom_position(perm, perm_index, copy_index);
+            if debug {
+                eprintln!(
+                    "{:?}: band {} copy {} (flipped {}) reaches bottom position {} of {} slots, n = {}, turning {:?}",
+                    strand.strand, perm_index, copy_index, flipped, absolute_index, slots, n, direction
+                );
+            }
+
+            match direction {
+                Direction::Left if absolute_index + n < slots => absolute_index += n,
+                Direction::Right if absolute_index >= n => absolute_index -= n,
+                Direction::Left => {
+                    let out_strand = Strand::Transverse(slots - absolute_index - 1);
+                    if debug {
+                        eprintln!(
+                            "    {} + {} >= {}: transverse index {} - {} - 1 -> {:?}",
+                            absolute_index, n, slots, slots, absolute_index, out_strand
+                        );
+                    }
+                    return (PyStrand { strand: out_strand }, flipped);
+                }
+                Direction::Right => {
+                    let out_strand = Strand::Transverse(absolute_index);
+                    if debug {
+                        eprintln!(
+                            "    {} < {}: transverse index {} -> {:?}",
+                            absolute_index, n, absolute_index, out_strand
+                        );
+                    }
+                    return (PyStrand { strand: out_strand }, flipped);
+                }
+            }
+            let out_strand = layout.top_strand(absolute_index);
+            if debug {
+                eprintln!(
+                    "    shifted to top position {} -> {:?}",
+                    absolute_index, out_strand
+                );
+            }
+            PyStrand { strand: out_strand }
+        }
+        Strand::Transverse(mut index) => {
+            if perm.transverse_flip {
+                index = n - index - 1;
+                flipped = 1;
+            }
+
+            let out_strand = if index + slots < n {
+                Strand::Transverse(index + slots)
+            } else {
+                let absolute_index = match direction {
+                    Direction::Left => n - index - 1,
+                    Direction::Right => slots + index - n,
+                };
+                layout.top_strand(absolute_index)
+            };
+            if debug {
+                eprintln!(
+                    "{:?}: index {} (flipped {}) with {} slots, n = {}, turning {:?} -> {:?}",
+                    strand.strand, index, flipped, slots, n, direction, out_strand
+                );
+            }
+            PyStrand { strand: out_strand }
+        }
+    };
+    (out_strand, flipped)
+}
+
+/// Division by a fixed divisor through a precomputed 128-bit reciprocal, exact for every u64
+/// numerator (Lemire, Kaser and Kurz, "Faster remainder by direct computation")
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Divisor {
+    divisor: u64,
+    /// ceil(2^128 / divisor), which wraps to 0 for a divisor of 1
+    reciprocal: u128,
+}
+
+/// Product of a u128 and a u64 shifted right by 128 bits, i.e. the top 64 bits of the 192-bit
+/// product
+fn mul_high(x: u128, y: u64) -> u128 {
+    let y = y as u128;
+    ((x >> 64) * y + (((x as u64) as u128 * y) >> 64)) >> 64
+}
+
+impl Divisor {
+    pub(crate) fn new(divisor: u64) -> Self {
+        Divisor {
+            divisor,
+            reciprocal: (u128::MAX / divisor as u128).wrapping_add(1),
+        }
+    }
+
+    /// Quotient and remainder of the division of `x` by the divisor
+    pub(crate) fn div_rem(&self, x: u64) -> (u64, u64) {
+        if self.divisor == 1 {
+            return (x, 0);
+        }
+        let quotient = mul_high(self.reciprocal, x) as u64;
+        let remainder = mul_high(self.reciprocal.wrapping_mul(x as u128), self.divisor) as u64;
+        (quotient, remainder)
+    }
+}
+
+/// `next_major_strand` with the arithmetic depending only on `(m, n)` done once, for the hot loops
+/// of the traversals. With uniform multiplicities, a copy moving along the bottom of the annulus
+/// carries its block and copy index forward, n being q blocks and r copies, and the slot of a
+/// transverse strand coming back to the bands is split with the reciprocal of m, so that no step
+/// divides. Other layouts, and debugging, go through `next_major_strand`.
+pub(crate) struct Stepper<'a> {
+    perm: &'a SignedPermutation,
+    layout: &'a SlotLayout,
+    n: u64,
+    slots: u64,
+    direction: Direction,
+    /// m, the quotient and remainder of n by m, and the reciprocal of m, when they are used
+    uniform: Option<(u64, u64, u64, Divisor)>,
+}
+
+impl<'a> Stepper<'a> {
+    pub(crate) fn new(
+        perm: &'a SignedPermutation,
+        layout: &'a SlotLayout,
+        n: u64,
+        direction: Direction,
+    ) -> Self {
+        let uniform = match layout {
+            SlotLayout::Uniform(m) if *m > 0 && !DEBUG.load(Ordering::Relaxed) => {
+                Some((*m, n / m, n % m, Divisor::new(*m)))
+            }
+            _ => None,
+        };
+        Stepper {
+            perm,
+            layout,
+            n,
+            slots: layout.slots(perm),
+            direction,
+            uniform,
+        }
+    }
+
+    /// Same as `next_major_strand`
+    #[inline]
+    pub(crate) fn next(&self, strand: PyStrand) -> (PyStrand, usize) {
+        let (m, blocks, copies, divisor) = match self.uniform {
+            Some(uniform) => uniform,
+            None => {
+                return next_major_strand(self.perm, self.layout, self.n, strand, self.direction)
+            }
+        };
+        let n = self.n;
+        let length = self.perm.permutation.len() as u64;
+        let (out_strand, flipped) = match strand.strand {
+            Strand::PermutationDirection(band, copy) => {
+                let flipped = self.perm.flipped[band];
+                let copy = if flipped { m - copy - 1 } else { copy };
+                let block = self.perm.permutation[band] as u64;
+                let out_strand = match self.direction {
+                    Direction::Left => {
+                        let (carry, shifted) = if copy + copies >= m {
+                            (1, copy + copies - m)
+                        } else {
+                            (0, copy + copies)
+                        };
+                        match (block + carry).checked_add(blocks) {
+                            Some(shifted_block) if shifted_block < length => {
+                                Strand::PermutationDirection(shifted_block as usize, shifted)
+                            }
+                            _ => Strand::Transverse(self.slots - (m * block + copy) - 1),
+                        }
+                    }
+                    Direction::Right => {
+                        let (borrow, shifted) = if copy >= copies {
+                            (0, copy - copies)
+                        } else {
+                            (1, copy + m - copies)
+                        };
+                        match block.checked_sub(blocks + borrow) {
+                            Some(shifted_block) => {
+                                Strand::PermutationDirection(shifted_block as usize, shifted)
+                            }
+                            None => Strand::Transverse(m * block + copy),
+                        }
+                    }
+                };
+                (out_strand, flipped as usize)
+            }
+            Strand::Transverse(index) => {
+                let flipped = self.perm.transverse_flip;
+                let index = if flipped { n - index - 1 } else { index };
+                let out_strand = if index + self.slots < n {
+                    Strand::Transverse(index + self.slots)
+                } else {
+                    let absolute_index = match self.direction {
+                        Direction::Left => n - index - 1,
+                        Direction::Right => self.slots + index - n,
+                    };
+                    let (block, copy) = divisor.div_rem(absolute_index);
+                    Strand::PermutationDirection(block as usize, copy)
+                };
+                (out_strand, flipped as usize)
+            }
+        };
+        (PyStrand { strand: out_strand }, flipped)
+    }
+}
+
+/// Inverse of `next_major_strand`, returning the strand sent to `strand` and whether that step
+/// flipped
+pub(crate) fn previous_major_strand(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    strand: PyStrand,
+    direction: Direction,
+) -> (PyStrand, usize) {
+    let slots = layout.slots(perm);
+    // The predecessor either comes back to the bottom of the annulus at a slot, or is a
+    // transverse strand with the index it has after going around the annulus
+    let bottom = match (strand.strand, direction) {
+        (Strand::PermutationDirection(band, copy), Direction::Left) => {
+            let position = layout.top_position(band, copy);
+            if position >= n {
+                Ok(position - n)
+            } else {
+                Err(n - position - 1)
+            }
+        }
+        (Strand::PermutationDirection(band, copy), Direction::Right) => {
+            let position = layout.top_position(band, copy);
+            if position + n < slots {
+                Ok(position + n)
+            } else {
+                Err(position + n - slots)
+            }
+        }
+        (Strand::Transverse(index), Direction::Left) if index < slots => Ok(slots - index - 1),
+        (Strand::Transverse(index), Direction::Right) if index < slots => Ok(index),
+        (Strand::Transverse(index), _) => Err(index - slots),
+    };
+    match bottom {
+        Ok(position) => {
+            let (band, mut copy) = layout.bottom_strand(perm, position);
+            let mut flipped = 0;
+            if perm.flipped[band] {
+                copy = layout.copies(band) - copy - 1;
+                flipped = 1;
+            }
+            (
+                PyStrand {
+                    strand: Strand::PermutationDirection(band, copy),
+                },
+                flipped,
+            )
+        }
+        Err(mut index) => {
+            let mut flipped = 0;
+            if perm.transverse_flip {
+                index = n - index - 1;
+                flipped = 1;
+            }
+            (
+                PyStrand {
+                    strand: Strand::Transverse(index),
+                },
+                flipped,
+            )
+        }
+    }
+}
+
+/// Traces the orbit of `start`, returning whether it runs through every strand together with its
+/// orientability, or failing if the orbit runs for more than `max_steps` strands
+pub(crate) fn orbit_connectivity(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    start: PyStrand,
+    direction: Direction,
+    max_steps: Option<u64>,
+) -> Result<(bool, usize), StepLimitExceeded> {
+    let expected_orbit_length = layout.slots(perm) + n;
+    let stepper = Stepper::new(perm, layout, n, direction);
+    let mut actual_orbit_length = 1;
+    check_steps(actual_orbit_length, max_steps)?;
+    let mut next_strand_with_orientability = stepper.next(start);
+    let mut next_strand = next_strand_with_orientability.0;
+    let mut orientability = next_strand_with_orientability.1;
+    while next_strand != start {
+        next_strand_with_orientability = stepper.next(next_strand);
+        next_strand = next_strand_with_orientability.0;
+        orientability = (orientability + next_strand_with_orientability.1) % 2;
+        actual_orbit_length += 1;
+        check_steps(actual_orbit_length, max_steps)?;
+    }
+    Ok((expected_orbit_length == actual_orbit_length, orientability))
+}
+
+/// Unchecked version of `count_components_with_orientability`
+pub(crate) fn component_counts(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    direction: Direction,
+) -> (u64, u64) {
+    unbounded(component_counts_bounded(perm, layout, n, direction, None))
+}
+
+/// Same as `component_counts`, failing if an orbit runs for more than `max_steps` strands
+pub(crate) fn component_counts_bounded(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    direction: Direction,
+    max_steps: Option<u64>,
+) -> Result<(u64, u64), StepLimitExceeded> {
+    let mut counts = (0, 0);
+    trace_components_bounded(
+        perm,
+        layout,
+        n,
+        direction,
+        max_steps,
+        &mut counts,
+        |_, _| {},
+        |counts, _, orientability| {
+            if orientability == 0 {
+                counts.0 += 1;
+            } else {
+                counts.1 += 1;
+            }
+        },
+    )?;
+    Ok(counts)
+}
+
+/// Total number of components of the resolved multicurve, tracing the orbits without keeping
+/// track of their orientability
+pub(crate) fn component_total(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    direction: Direction,
+) -> u64 {
+    let mut total = 0;
+    trace_components(
+        perm,
+        layout,
+        n,
+        direction,
+        &mut total,
+        |_, _| {},
+        |total, _, _| *total += 1,
+    );
+    total
+}
+
+/// Traces every component of the resolved multicurve once, calling `visit` on each of its
+/// strands and then `f` with its orbit length and orientability (0 for two-sided, 1 for
+/// one-sided). Components are visited in the order of their smallest strand. Both callbacks
+/// share the mutable `state`.
+pub(crate) fn trace_components<S>(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    direction: Direction,
+    state: &mut S,
+    visit: impl FnMut(&mut S, PyStrand),
+    f: impl FnMut(&mut S, u64, usize),
+) {
+    unbounded(trace_components_bounded(
+        perm, layout, n, direction, None, state, visit, f,
+    ))
+}
+
+/// Same as `trace_components`, failing if an orbit runs for more than `max_steps` strands
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn trace_components_bounded<S>(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    direction: Direction,
+    max_steps: Option<u64>,
+    state: &mut S,
+    mut visit: impl FnMut(&mut S, PyStrand),
+    mut f: impl FnMut(&mut S, u64, usize),
+) -> Result<(), StepLimitExceeded> {
+    let mut strands = VisitedStrands::new(perm, layout, n);
+    let stepper = Stepper::new(perm, layout, n, direction);
+
+    while let Some(first_strand) = strands.pop_first() {
+        visit(state, first_strand);
+        let next_strand_with_orientability = stepper.next(first_strand);
+        let mut orientability = next_strand_with_orientability.1;
+        let mut next_strand = next_strand_with_orientability.0;
+        let mut length = 1;
+        check_steps(length, max_steps)?;
+        while next_strand != first_strand {
+            visit(state, next_strand);
+            let next_strand_with_orientability = stepper.next(next_strand);
+            strands.insert(next_strand);
+            orientability += next_strand_with_orientability.1;
+            next_strand = next_strand_with_orientability.0;
+            length += 1;
+            check_steps(length, max_steps)?;
+        }
+        f(state, length, orientability % 2);
+    }
+    Ok(())
+}
+
+/// Returns the orientability of the resolved multicurve if it has a single component
+pub(crate) fn connected_orientability(
+    perm: &SignedPermutation,
+    layout: &SlotLayout,
+    n: u64,
+    direction: Direction,
+) -> Option<usize> {
+    let starting_strand = layout.strands(perm, n).next()?;
+    let stepper = Stepper::new(perm, layout, n, direction);
+    let mut orbit_length = 0;
+    let mut orientability = 0;
+    let mut strand = starting_strand;
+    loop {
+        let (next_strand, flipped) = stepper.next(strand);
+        strand = next_strand;
+        orientability ^= flipped;
+        orbit_length += 1;
+        if strand == starting_strand {
+            break;
+        }
+    }
+    if orbit_length == layout.slots(perm) + n {
+        Some(orientability)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Divisor;
+
+    #[test]
+    fn divisor_matches_division() {
+        let mut values = vec![0, 1, 2, 3, u64::MAX, u64::MAX - 1, u64::MAX / 2];
+        for shift in 0..64 {
+            let power = 1u64 << shift;
+            values.extend([power - 1, power, power + 1].iter().copied());
+        }
+        for &divisor in values.iter().filter(|divisor| **divisor != 0) {
+            let fast = Divisor::new(divisor);
+            for &x in &values {
+                assert_eq!(
+                    fast.div_rem(x),
+                    (x / divisor, x % divisor),
+                    "{} / {}",
+                    x,
+                    divisor
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Dihedral symmetries of the labels
+//!
+//! Rotating the labels cyclically moves the point where the transverse strands begin around the
+//! annulus, and leaves the resolved multicurves unchanged. Reversing the labels reflects the
+//! picture, which exchanges resolving to the left and to the right. Together they give an action
+//! of the dihedral group of order `2 * len` by relabeling, where the element `(k, reversed)` first
+//! rotates every label j to j + k modulo len and then, if `reversed`, sends it to len - 1 - j.
+
+use crate::SignedPermutation;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Image of `perm` under the element `(rotation, reversed)` of the dihedral group
+fn dihedral_image(perm: &SignedPermutation, rotation: usize, reversed: bool) -> SignedPermutation {
+    let length = perm.permutation.len();
+    let relabeling = (0..length)
+        .map(|letter| {
+            let rotated = (letter + rotation) % length;
+            if reversed {
+                length - 1 - rotated
+            } else {
+                rotated
+            }
+        })
+        .collect();
+    perm.conjugate(relabeling)
+        .expect("a rotation or reflection of the labels is a permutation")
+}
+
+/// Every element of the dihedral group with its image of `perm`
+fn dihedral_orbit(
+    perm: &SignedPermutation,
+) -> impl Iterator<Item = ((usize, bool), SignedPermutation)> + '_ {
+    let rotations = perm.permutation.len().max(1);
+    [false, true].iter().flat_map(move |&reversed| {
+        (0..rotations).map(move |rotation| {
+            let image = dihedral_image(perm, rotation, reversed);
+            ((rotation, reversed), image)
+        })
+    })
+}
+
+/// Sort key of a signed permutation, used to pick the canonical element of an orbit
+fn order_key(perm: &SignedPermutation) -> (Vec<usize>, Vec<usize>) {
+    let mut flips: Vec<usize> = perm.flip_set.iter().copied().collect();
+    flips.sort_unstable();
+    (perm.permutation.clone(), flips)
+}
+
+/// Whether `perm` is the smallest of its rotations, which all give the same resolved multicurves
+pub(crate) fn is_rotation_canonical(perm: &SignedPermutation) -> bool {
+    let key = order_key(perm);
+    (1..perm.permutation.len())
+        .all(|rotation| key <= order_key(&dihedral_image(perm, rotation, false)))
+}
+
+/// Returns the relabeling `(rotation, reversed)` sending `perm` to `other`, or `None` if they are
+/// not equivalent. When `reversed` is set, resolving `other` to the right gives the same
+/// multicurves as resolving `perm` to the left.
+#[pyfunction]
+fn find_dihedral_equivalence(
+    perm: &SignedPermutation,
+    other: &SignedPermutation,
+) -> Option<(usize, bool)> {
+    if perm.permutation.len() != other.permutation.len() {
+        return None;
+    }
+    dihedral_orbit(perm)
+        .find(|(_, image)| image == other)
+        .map(|(element, _)| element)
+}
+
+/// Lists the relabelings `(rotation, reversed)` fixing `perm`, always including `(0, False)`
+#[pyfunction]
+fn dihedral_symmetries(perm: &SignedPermutation) -> Vec<(usize, bool)> {
+    dihedral_orbit(perm)
+        .filter(|(_, image)| image == perm)
+        .map(|(element, _)| element)
+        .collect()
+}
+
+/// Returns the smallest image of `perm` under the dihedral group, ordered by the permutation and
+/// then the sorted flips, together with the relabeling `(rotation, reversed)` giving it
+#[pyfunction]
+fn dihedral_canonical_form(perm: &SignedPermutation) -> (SignedPermutation, usize, bool) {
+    let ((rotation, reversed), image) = dihedral_orbit(perm)
+        .min_by_key(|(_, image)| order_key(image))
+        .unwrap();
+    (image, rotation, reversed)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(find_dihedral_equivalence, m)?)?;
+    m.add_function(wrap_pyfunction!(dihedral_symmetries, m)?)?;
+    m.add_function(wrap_pyfunction!(dihedral_canonical_form, m)?)?;
+    Ok(())
+}
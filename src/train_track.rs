@@ -0,0 +1,151 @@
+//! Train track dual to the configuration of curves
+//!
+//! Smoothing every crossing of the transverse curve γ with the permutation direction curve δ in
+//! the direction of the resolution gives a train track carrying the resolved multicurve. Crossing
+//! p sits where δ crosses γ at block p, coming out of the band ending at bottom block p and going
+//! into band p. The track has one branch for each band, one for each arc of γ between consecutive
+//! crossings, and one short branch for each smoothed crossing, with a switch at either end of it.
+
+use crate::{Direction, Multiplicity, SignedPermutation};
+use pyo3::exceptions::PyOverflowError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+/// Python class to represent a train track, with branches numbered as the bands, then the arcs
+/// of the transverse curve, then the smoothed crossings
+#[pyclass]
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct TrainTrack {
+    length: usize,
+    /// Band whose copies end at each bottom block
+    band_ending_at: Vec<usize>,
+    /// Large and small branches at each switch
+    switch_branches: Vec<(Vec<usize>, Vec<usize>)>,
+}
+
+impl TrainTrack {
+    fn new(perm: &SignedPermutation, direction: Direction) -> Self {
+        let length = perm.permutation.len();
+        let mut band_ending_at = vec![0; length];
+        for (band, bottom) in perm.permutation.iter().enumerate() {
+            band_ending_at[*bottom] = band;
+        }
+
+        let transverse_arc = |p: usize| length + p % length;
+        let mut switch_branches = Vec::with_capacity(2 * length);
+        for (p, incoming_band) in band_ending_at.iter().enumerate() {
+            let crossing = 2 * length + p;
+            // Turning left, δ merges with the arc of γ coming into the crossing, and splits off
+            // from the arc leaving it. Turning right, the arcs are swapped.
+            let (arc_in, arc_out) = match direction {
+                Direction::Left => (transverse_arc(p + length - 1), transverse_arc(p)),
+                Direction::Right => (transverse_arc(p), transverse_arc(p + length - 1)),
+            };
+            switch_branches.push((vec![crossing], vec![*incoming_band, arc_in]));
+            switch_branches.push((vec![crossing], vec![p, arc_out]));
+        }
+
+        Self {
+            length,
+            band_ending_at,
+            switch_branches,
+        }
+    }
+
+    fn branch_count(&self) -> usize {
+        // Without any crossings γ is a single closed branch
+        (3 * self.length).max(1)
+    }
+}
+
+#[pymethods]
+impl TrainTrack {
+    /// Type and index of each branch: `'p'` for the band of a letter, `'t'` for an arc of the
+    /// transverse curve and `'c'` for a smoothed crossing
+    #[getter]
+    fn branches(&self) -> Vec<(char, usize)> {
+        if self.length == 0 {
+            return vec![('t', 0)];
+        }
+        let mut branches = Vec::with_capacity(3 * self.length);
+        for kind in ['p', 't', 'c'] {
+            branches.extend((0..self.length).map(|index| (kind, index)));
+        }
+        branches
+    }
+
+    /// Large and small branches at each switch
+    #[getter]
+    fn switches(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        self.switch_branches.clone()
+    }
+
+    /// Switch conditions as rows of coefficients, one for each switch, each saying that the
+    /// weight of the large branches equals the weight of the small branches
+    #[getter]
+    fn switch_conditions(&self) -> Vec<Vec<isize>> {
+        self.switch_branches
+            .iter()
+            .map(|(large, small)| {
+                let mut row = vec![0; self.branch_count()];
+                for branch in large {
+                    row[*branch] += 1;
+                }
+                for branch in small {
+                    row[*branch] -= 1;
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// Weights of the branches for m copies of each band and n copies of the transverse curve.
+    /// Each crossing carries the weight entering it, `m` being either a single multiplicity or
+    /// one for each letter of the permutation.
+    fn measure(&self, m: Multiplicity, n: u64) -> PyResult<Vec<u64>> {
+        let copies = m.per_band(self.length)?;
+        if self.length == 0 {
+            return Ok(vec![n]);
+        }
+        let mut weights = copies.clone();
+        weights.resize(2 * self.length, n);
+        for band in &self.band_ending_at {
+            let weight = copies[*band].checked_add(n).ok_or_else(|| {
+                PyOverflowError::new_err(format!("Branch weight m + n overflows for n = {}", n))
+            })?;
+            weights.push(weight);
+        }
+        Ok(weights)
+    }
+
+    /// Determines whether the weights given by `measure(m, n)` satisfy every switch condition
+    fn is_admissible_measure(&self, m: Multiplicity, n: u64) -> PyResult<bool> {
+        let weights = self.measure(m, n)?;
+        let total = |branches: &[usize]| branches.iter().map(|b| weights[*b] as u128).sum::<u128>();
+        Ok(self
+            .switch_branches
+            .iter()
+            .all(|(large, small)| total(large) == total(small)))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "TrainTrack({} branches, {} switches)",
+            self.branch_count(),
+            self.switch_branches.len()
+        ))
+    }
+}
+
+/// Builds the train track obtained by smoothing every crossing of the original curves
+#[pyfunction]
+#[pyo3(signature = (perm, direction = Direction::Left))]
+fn to_train_track(perm: &SignedPermutation, direction: Direction) -> TrainTrack {
+    TrainTrack::new(perm, direction)
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<TrainTrack>()?;
+    m.add_function(wrap_pyfunction!(to_train_track, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Dehn twists about the original curves
+//!
+//! Twisting the resolved multicurve about the transverse curve γ adds a copy of γ for each of
+//! its crossings with γ, i.e. for each permutation direction strand. Twisting about the
+//! permutation direction curve δ adds to each band n copies for every band in its component of
+//! δ. Resolving to the right is the mirror image of resolving to the left, so a right resolution
+//! behaves like a negative number of copies, and a twist turns it into a left resolution once
+//! the count becomes positive.
+
+use crate::{Direction, Multiplicity, SignedPermutation, SlotLayout};
+use pyo3::exceptions::{PyOverflowError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::convert::TryFrom;
+
+/// Number of copies with the sign of the direction they are resolved in
+fn signed(count: u64, direction: Direction) -> i128 {
+    match direction {
+        Direction::Left => count as i128,
+        Direction::Right => -(count as i128),
+    }
+}
+
+/// Inverse of `signed`, resolving zero copies to the left
+fn unsigned(value: i128) -> PyResult<(u64, Direction)> {
+    let direction = if value < 0 {
+        Direction::Right
+    } else {
+        Direction::Left
+    };
+    let count = u64::try_from(value.unsigned_abs())
+        .map_err(|_| PyOverflowError::new_err(format!("Number of copies {} overflows", value)))?;
+    Ok((count, direction))
+}
+
+/// Applies the `power`-th power of the Dehn twist about the transverse curve to the multicurve
+/// obtained by resolving `(perm, m, n)` in `direction`. Positive powers twist to the left.
+/// Returns the new `(m, n, direction)`, the permutation staying the same.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, power = 1, direction = Direction::Left))]
+fn twist_transverse(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    power: isize,
+    direction: Direction,
+) -> PyResult<(Multiplicity, u64, Direction)> {
+    if perm.transverse_flip {
+        return Err(PyValueError::new_err(
+            "Cannot twist about a one-sided transverse curve",
+        ));
+    }
+    let layout = SlotLayout::new(perm, m.clone(), n)?;
+    let crossings = layout.slots(perm) as i128;
+    let (n, direction) = unsigned(signed(n, direction) + power as i128 * crossings)?;
+    SlotLayout::new(perm, m.clone(), n)?;
+    Ok((m, n, direction))
+}
+
+/// Applies the `power`-th power of the Dehn twist about the permutation direction curve to the
+/// multicurve obtained by resolving `(perm, m, n)` in `direction`. Positive powers twist to the
+/// left. Returns the new `(m, n, direction)`, where `m` becomes a list if the components of the
+/// permutation direction curve have different lengths.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, power = 1, direction = Direction::Left))]
+fn twist_permutation_direction(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    power: isize,
+    direction: Direction,
+) -> PyResult<(Multiplicity, u64, Direction)> {
+    if !perm.flip_set.is_empty() {
+        return Err(PyValueError::new_err(
+            "Twisting about the permutation direction curve requires a permutation without flips",
+        ));
+    }
+    SlotLayout::new(perm, m.clone(), n)?;
+    let length = perm.permutation.len();
+    let uniform = matches!(m, Multiplicity::Uniform(_));
+    let copies = m.clone().per_band(length)?;
+
+    // Band j continues into band perm(j), so the components of δ are the cycles of perm
+    let mut cycle_length = vec![0; length];
+    for start in 0..length {
+        if cycle_length[start] != 0 {
+            continue;
+        }
+        let mut cycle = vec![start];
+        let mut band = perm.permutation[start];
+        while band != start {
+            cycle.push(band);
+            band = perm.permutation[band];
+        }
+        for band in &cycle {
+            cycle_length[*band] = cycle.len();
+        }
+    }
+
+    let mut new_copies = Vec::with_capacity(length);
+    let mut new_direction = None;
+    for (count, cycle_length) in copies.into_iter().zip(cycle_length) {
+        let twisted = signed(count, direction) + power as i128 * n as i128 * cycle_length as i128;
+        let (count, band_direction) = unsigned(twisted)?;
+        if count != 0 {
+            match new_direction {
+                Some(other) if other != band_direction => {
+                    return Err(PyValueError::new_err(
+                        "Twisted multicurve resolves different bands in different directions",
+                    ))
+                }
+                _ => new_direction = Some(band_direction),
+            }
+        }
+        new_copies.push(count);
+    }
+
+    let m = match new_copies.first() {
+        Some(first) if uniform && new_copies.iter().all(|count| count == first) => {
+            Multiplicity::Uniform(*first)
+        }
+        None => m,
+        _ => Multiplicity::PerBand(new_copies),
+    };
+    SlotLayout::new(perm, m.clone(), n)?;
+    Ok((m, n, new_direction.unwrap_or(Direction::Left)))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(twist_transverse, m)?)?;
+    m.add_function(wrap_pyfunction!(twist_permutation_direction, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! Configurations given by two multicurves meeting in a prescribed pattern
+//!
+//! A regular neighbourhood of the union of two multicurves α and β is made of one square around
+//! each intersection point, glued along their horizontal sides following α and along their vertical
+//! sides following β. The horizontal signed permutation sends the square of an intersection point
+//! to the next one along α, and the vertical one to the next one along β, flipping the gluing when
+//! the band between them is twisted. With n copies of α and m copies of β, every square holds a
+//! grid of n horizontal and m vertical strands, all resolved the same way in the frame of the
+//! square, so that the entries on its bottom and left sides are sent in order to the exits on its
+//! top and right sides. The resolved multicurve is traced with one transition map per gluing.
+//!
+//! When the horizontal permutation is the cycle 0 -> 1 -> ... -> k-1 -> 0, flipped at k-1 if the
+//! transverse strands are, this is the configuration of the vertical signed permutation.
+
+use crate::results::ComponentCount;
+use crate::{narrow, Direction, SignedPermutation};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Strand leaving a square, through its top side at a position counted from the left, or through
+/// the side it moves towards horizontally at a position counted from the top
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Exit {
+    Top(usize, u64),
+    Side(usize, u64),
+}
+
+/// Surgery along two multicurves meeting in the squares of a pair of signed permutations
+#[pyclass]
+#[derive(Debug, Clone)]
+struct TwoPermutationConfiguration {
+    horizontal: SignedPermutation,
+    vertical: SignedPermutation,
+    /// Square glued to the left side of each square along α
+    horizontal_inverse: Vec<usize>,
+}
+
+impl TwoPermutationConfiguration {
+    /// Next exit along the resolved multicurve, and whether the gluing crossed is flipped
+    fn next_exit(&self, m: u64, n: u64, exit: Exit, direction: Direction) -> (Exit, bool) {
+        // Index of the entry of the next square along its bottom and left sides, or along its
+        // bottom and right sides when turning right, listed so that entry i leaves through exit i
+        let (square, entry, flipped) = match exit {
+            Exit::Top(square, position) => {
+                let next = self.vertical.permutation[square];
+                let flipped = self.vertical.flipped[square];
+                let position = if flipped { m - position - 1 } else { position };
+                let entry = match direction {
+                    Direction::Left => n + position,
+                    Direction::Right => n + m - position - 1,
+                };
+                (next, entry, flipped)
+            }
+            Exit::Side(square, height) => {
+                // Square whose right side is crossed, and the square entered
+                let (glued, next) = match direction {
+                    Direction::Left => (square, self.horizontal.permutation[square]),
+                    Direction::Right => {
+                        let previous = self.horizontal_inverse[square];
+                        (previous, previous)
+                    }
+                };
+                let flipped = self.horizontal.flipped[glued];
+                let height = if flipped { n - height - 1 } else { height };
+                (next, height, flipped)
+            }
+        };
+        let out = if entry >= m {
+            Exit::Side(square, entry - m)
+        } else {
+            match direction {
+                Direction::Left => Exit::Top(square, entry),
+                Direction::Right => Exit::Top(square, m - entry - 1),
+            }
+        };
+        (out, flipped)
+    }
+
+    /// Numbers of two-sided and one-sided components of the resolution
+    fn component_counts(&self, m: u64, n: u64, direction: Direction) -> PyResult<(u64, u64)> {
+        let squares = self.horizontal.permutation.len();
+        let per_square = narrow(m.checked_add(n).ok_or_else(|| {
+            PyValueError::new_err("Too many strands in each square of the configuration")
+        })?)?;
+        let index = |exit: Exit| match exit {
+            Exit::Top(square, position) => square * per_square + position as usize,
+            Exit::Side(square, height) => square * per_square + (m + height) as usize,
+        };
+        let mut visited = vec![false; squares * per_square];
+        let mut counts = (0, 0);
+        for square in 0..squares {
+            let exits = (0..m)
+                .map(|position| Exit::Top(square, position))
+                .chain((0..n).map(|height| Exit::Side(square, height)));
+            for start in exits {
+                if visited[index(start)] {
+                    continue;
+                }
+                let mut orientability = false;
+                let mut exit = start;
+                while !visited[index(exit)] {
+                    visited[index(exit)] = true;
+                    let (next, flipped) = self.next_exit(m, n, exit, direction);
+                    orientability ^= flipped;
+                    exit = next;
+                }
+                if orientability {
+                    counts.1 += 1;
+                } else {
+                    counts.0 += 1;
+                }
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[pymethods]
+impl TwoPermutationConfiguration {
+    /// Takes the signed permutations following α and β through the intersection points, which
+    /// must have the same length and no transverse flip
+    #[new]
+    fn new(horizontal: SignedPermutation, vertical: SignedPermutation) -> PyResult<Self> {
+        if horizontal.permutation.len() != vertical.permutation.len() {
+            return Err(PyValueError::new_err(format!(
+                "Both signed permutations must have the same length, not {} and {}",
+                horizontal.permutation.len(),
+                vertical.permutation.len()
+            )));
+        }
+        if horizontal.transverse_flip || vertical.transverse_flip {
+            return Err(PyValueError::new_err(
+                "The signed permutations of a two-permutation configuration have no transverse flip",
+            ));
+        }
+        let mut horizontal_inverse = vec![0; horizontal.permutation.len()];
+        for (square, next) in horizontal.permutation.iter().enumerate() {
+            horizontal_inverse[*next] = square;
+        }
+        Ok(TwoPermutationConfiguration {
+            horizontal,
+            vertical,
+            horizontal_inverse,
+        })
+    }
+
+    #[getter]
+    fn horizontal(&self) -> SignedPermutation {
+        self.horizontal.clone()
+    }
+
+    #[getter]
+    fn vertical(&self) -> SignedPermutation {
+        self.vertical.clone()
+    }
+
+    /// Counts the two-sided and one-sided components of the resolution of m copies of β and n
+    /// copies of α
+    #[pyo3(signature = (m, n, direction = Direction::Left))]
+    fn count_components_with_orientability(
+        &self,
+        py: Python,
+        m: u64,
+        n: u64,
+        direction: Direction,
+    ) -> PyResult<ComponentCount> {
+        py.detach(|| self.component_counts(m, n, direction))
+            .map(ComponentCount::from)
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "TwoPermutationConfiguration({}, {})",
+            self.horizontal.__repr__()?,
+            self.vertical.__repr__()?
+        ))
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<TwoPermutationConfiguration>()?;
+    Ok(())
+}
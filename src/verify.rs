@@ -0,0 +1,136 @@
+//! Component counting with its invariants cross-checked
+//!
+//! Meant as a trusted reference when modifying the model: the counts are traced once while
+//! checking that the orbits partition the strands, and compared with the other ways of
+//! computing them, failing loudly on the first inconsistency.
+
+use crate::results::ComponentCount;
+use crate::{
+    cached_component_counts, connected_orientability, orbit_connectivity, trace_components_bounded,
+    Direction, Multiplicity, PyStrand, SignedPermutation, SlotLayout,
+};
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::collections::HashSet;
+
+create_exception!(counting_components, VerificationError, PyRuntimeError);
+
+/// Fails with `message` unless `condition` holds
+fn ensure(condition: bool, message: impl FnOnce() -> String) -> PyResult<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(VerificationError::new_err(message()))
+    }
+}
+
+/// Same as `count_components_with_orientability`, additionally checking that every strand is
+/// visited exactly once, that the orbit lengths sum to the number of strands, and that the
+/// counts agree with `has_one_component`, the connectivity test of the sweeps and the cache.
+/// Raises `VerificationError` describing the first inconsistency found.
+#[pyfunction]
+#[pyo3(signature = (perm, m, n, direction = Direction::Left))]
+fn count_components_checked(
+    perm: &SignedPermutation,
+    m: Multiplicity,
+    n: u64,
+    direction: Direction,
+) -> PyResult<ComponentCount> {
+    let layout = SlotLayout::new(perm, m, n)?;
+    let strand_total = layout.slots(perm) + n;
+
+    // Visited strands, the first strand visited twice or out of range, and the orbit lengths
+    let mut state = (HashSet::new(), None, Vec::new());
+    let traced = trace_components_bounded(
+        perm,
+        &layout,
+        n,
+        direction,
+        Some(strand_total),
+        &mut state,
+        |(visited, invalid, _): &mut (HashSet<PyStrand>, Option<PyStrand>, _), strand| {
+            if (!visited.insert(strand) || !layout.contains(perm, n, strand)) && invalid.is_none() {
+                *invalid = Some(strand);
+            }
+        },
+        |(_, _, orbits), length, orientability| orbits.push((length, orientability)),
+    );
+    let (visited, invalid, orbits) = state;
+    if let Some(strand) = invalid {
+        return Err(VerificationError::new_err(format!(
+            "Strand {:?} is visited twice or does not exist",
+            strand.strand
+        )));
+    }
+    traced.map_err(|err| {
+        VerificationError::new_err(format!("{}, more than the number of strands", err))
+    })?;
+
+    let visited_total = visited.len() as u64;
+    ensure(visited_total == strand_total, || {
+        format!(
+            "Visited {} distinct strands out of {}",
+            visited_total, strand_total
+        )
+    })?;
+    let length_total: u64 = orbits.iter().map(|(length, _)| length).sum();
+    ensure(length_total == strand_total, || {
+        format!(
+            "Orbit lengths sum to {} instead of the {} strands",
+            length_total, strand_total
+        )
+    })?;
+
+    let one_sided = orbits
+        .iter()
+        .filter(|(_, orientability)| *orientability == 1)
+        .count() as u64;
+    let counts = (orbits.len() as u64 - one_sided, one_sided);
+    let single = match orbits[..] {
+        [(_, orientability)] => Some(orientability),
+        _ => None,
+    };
+
+    if let Some(start) = layout.strands(perm, n).next() {
+        let (connected, orientability) =
+            orbit_connectivity(perm, &layout, n, start, direction, Some(strand_total))?;
+        ensure(connected == single.is_some(), || {
+            format!(
+                "has_one_component gives connected = {} for {} components",
+                connected,
+                orbits.len()
+            )
+        })?;
+        if let Some(single) = single {
+            ensure(single == orientability, || {
+                format!(
+                    "has_one_component gives orientability {} for a single component of \
+                     orientability {}",
+                    orientability, single
+                )
+            })?;
+        }
+    }
+    let sweep_orientability = connected_orientability(perm, &layout, n, direction);
+    ensure(sweep_orientability == single, || {
+        format!(
+            "The connectivity test of the sweeps gives {:?} instead of {:?}",
+            sweep_orientability, single
+        )
+    })?;
+    if let SlotLayout::Uniform(m) = layout {
+        let cached = cached_component_counts(perm, m, n, direction);
+        ensure(cached == counts, || {
+            format!("Cached counts {:?} differ from {:?}", cached, counts)
+        })?;
+    }
+    Ok(counts.into())
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(count_components_checked, m)?)?;
+    m.add("VerificationError", m.py().get_type::<VerificationError>())?;
+    Ok(())
+}
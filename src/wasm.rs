@@ -0,0 +1,92 @@
+//! JavaScript bindings of the core counting functions
+//!
+//! Built with `--no-default-features --features wasm`, e.g. through `wasm-pack`. Permutations are
+//! passed as plain arrays in the same form as the Python constructor, with a single multiplicity
+//! m, and errors are thrown as JavaScript `Error`s with the messages of the Python exceptions.
+
+use crate::surgery::{
+    component_counts, orbit_connectivity, Direction, Multiplicity, PermutationError,
+    SignedPermutation, SlotLayout,
+};
+use wasm_bindgen::prelude::*;
+
+/// Whether the resolved multicurve is connected, with its orientability
+#[wasm_bindgen]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Connectivity {
+    pub connected: bool,
+    pub orientability: usize,
+}
+
+/// Numbers of two-sided and one-sided components
+#[wasm_bindgen]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ComponentCount {
+    #[wasm_bindgen(js_name = twoSided)]
+    pub two_sided: u64,
+    #[wasm_bindgen(js_name = oneSided)]
+    pub one_sided: u64,
+}
+
+/// Builds the permutation and the layout of m copies of every band with n transverse strands
+fn configuration(
+    permutation: Vec<usize>,
+    flips: Vec<usize>,
+    transverse_flip: bool,
+    m: u64,
+    n: u64,
+) -> Result<(SignedPermutation, SlotLayout), JsError> {
+    let perm = SignedPermutation::try_new(permutation, flips, transverse_flip)
+        .map_err(|err| JsError::new(&err.to_string()))?;
+    let layout = SlotLayout::new(&perm, Multiplicity::Uniform(m), n)
+        .map_err(|err| JsError::new(&err.to_string()))?;
+    Ok((perm, layout))
+}
+
+fn parse_direction(direction: &str) -> Result<Direction, JsError> {
+    direction
+        .parse()
+        .map_err(|err: PermutationError| JsError::new(&err.to_string()))
+}
+
+/// Same as the Python `has_one_component`, starting from the first strand
+#[wasm_bindgen(js_name = hasOneComponent)]
+pub fn has_one_component(
+    permutation: Vec<usize>,
+    flips: Vec<usize>,
+    transverse_flip: bool,
+    m: u64,
+    n: u64,
+    direction: &str,
+) -> Result<Connectivity, JsError> {
+    let direction = parse_direction(direction)?;
+    let (perm, layout) = configuration(permutation, flips, transverse_flip, m, n)?;
+    let (connected, orientability) = match layout.strands(&perm, n).next() {
+        Some(start) => orbit_connectivity(&perm, &layout, n, start, direction, None)
+            .map_err(|err| JsError::new(&err.to_string()))?,
+        None => (false, 0),
+    };
+    Ok(Connectivity {
+        connected,
+        orientability,
+    })
+}
+
+/// Same as the Python `count_components_with_orientability`
+#[wasm_bindgen(js_name = countComponentsWithOrientability)]
+pub fn count_components_with_orientability(
+    permutation: Vec<usize>,
+    flips: Vec<usize>,
+    transverse_flip: bool,
+    m: u64,
+    n: u64,
+    direction: &str,
+) -> Result<ComponentCount, JsError> {
+    let direction = parse_direction(direction)?;
+    let (perm, layout) = configuration(permutation, flips, transverse_flip, m, n)?;
+    let (two_sided, one_sided) = component_counts(&perm, &layout, n, direction);
+    Ok(ComponentCount {
+        two_sided,
+        one_sided,
+    })
+}